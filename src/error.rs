@@ -67,8 +67,30 @@ pub enum TdmsError {
         "The calculated size for a data chunk is grater than 2^64 bytes. This isn't allowed and probably indicates a corrupt file."
     )]
     ChunkSizeOverflow,
+    #[error(
+        "The interleaved data block has channels with different numbers of values. The file is likely corrupt."
+    )]
+    InterleavedLengthMismatch,
     #[error("DAQmx Channels are not supported yet")]
     DaqmxChannelsNotSupported,
+    #[error("The background writer has stopped")]
+    BackgroundWriterStopped,
+    #[error("The background writer stopped because of an error")]
+    BackgroundWriterFailed(#[source] std::sync::Arc<TdmsError>),
+    #[error("The background writer queue is full")]
+    WriteQueueFull,
+    #[error("A previous write failed or was cancelled so the file may contain part of a segment")]
+    WriterPoisoned,
+    #[error("The file is locked by another reader or writer")]
+    FileLocked,
+    #[error("The channel data cannot be borrowed without copying because {0}")]
+    ZeroCopyNotPossible(&'static str),
+    #[error("Channel {0} cannot be removed from a data block containing variable size data")]
+    VariableSizeChannelRemoval(String),
+    #[error("The length of the storage does not match the segments in it")]
+    StorageLengthMismatch,
+    #[error("Cannot rename {0} to {1} because {2}")]
+    InvalidRename(String, String, &'static str),
     #[cfg(feature = "chrono")]
     #[error("Failed to convert LVTime to chrono::DateTime")]
     ChronoDateTimeConversionFailed(#[source] labview_interop::types::timestamp::LVTimeError),
@@ -0,0 +1,480 @@
+//! Async reading of TDMS files using tokio.
+//!
+//! All the file access is awaited but the decoding is the same synchronous code as
+//! [`crate::TdmsFile`] working on bytes in memory. The metadata for each segment is loaded
+//! before it is parsed and the raw data is loaded a read span at a time. Blocks larger
+//! than a span are split so only the parts holding the requested channels are loaded,
+//! keeping the memory used by a read bounded.
+
+use std::io::{Cursor, SeekFrom};
+use std::path::Path;
+
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncSeek, AsyncSeekExt};
+
+use super::block_pieces::{BlockPiece, split_block};
+use super::channel_reader::{
+    BlockBuffer, BlockRead, ChannelProgress, MAX_SPAN_BYTES, MAX_SPAN_GAP, all_channels_complete,
+    get_block, plan_channels_read, plan_read_spans, read_block,
+};
+use crate::error::TdmsError;
+use crate::index::Index;
+use crate::io::data_types::TdmsStorageType;
+use crate::meta_data::{LEAD_IN_BYTES, Segment};
+use crate::{ChannelPath, PropertyPath, PropertyValue};
+
+/// A TDMS file read with tokio async IO.
+///
+/// This indexes the file in the same way as [`crate::TdmsFile`] and provides the same
+/// read methods, awaiting the file reads instead of blocking.
+///
+/// Dropping a read before it completes is safe. Each read seeks to the data it needs
+/// so the file can be read again afterwards.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use tedium::{AsyncTdmsFile, ChannelPath};
+///
+/// # async fn example() -> Result<(), tedium::TdmsError> {
+/// let mut file = AsyncTdmsFile::open("data.tdms").await?;
+/// let channel = ChannelPath::new("group", "channel");
+/// let length = file.channel_length(&channel).unwrap_or(0);
+/// let mut output = vec![0.0f64; length as usize];
+/// file.read_channel(&channel, &mut output).await?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct AsyncTdmsFile<F: AsyncRead + AsyncSeek + Unpin> {
+    index: Index,
+    file: F,
+}
+
+impl AsyncTdmsFile<tokio::fs::File> {
+    /// Open the file at the path and index the metadata ready for access.
+    pub async fn open(path: impl AsRef<Path>) -> Result<Self, TdmsError> {
+        let file = tokio::fs::File::open(path).await?;
+        Self::new(file).await
+    }
+}
+
+impl<F: AsyncRead + AsyncSeek + Unpin> AsyncTdmsFile<F> {
+    /// Index the TDMS data in the given stream.
+    pub async fn new(mut file: F) -> Result<Self, TdmsError> {
+        let index = build_index_async(&mut file).await?;
+        Ok(Self { index, file })
+    }
+
+    pub(super) fn from_parts(index: Index, file: F) -> Self {
+        Self { index, file }
+    }
+
+    pub(super) fn into_parts(self) -> (Index, F) {
+        (self.index, self.file)
+    }
+
+    /// Get the underlying stream.
+    pub fn into_inner(self) -> F {
+        self.file
+    }
+
+    /// Get the length of the channel.
+    pub fn channel_length(&self, channel: &ChannelPath) -> Option<u64> {
+        self.index.channel_length(channel)
+    }
+
+    /// Read the property by name from the full object path.
+    /// This will return `None` if the property does not exist.
+    pub fn read_property(
+        &self,
+        object_path: &PropertyPath,
+        property: &str,
+    ) -> Result<Option<&PropertyValue>, TdmsError> {
+        self.index.get_object_property(object_path, property)
+    }
+
+    /// Read all properties for the given object path.
+    pub fn read_all_properties(
+        &self,
+        object_path: &PropertyPath,
+    ) -> Option<impl Iterator<Item = (&String, &PropertyValue)>> {
+        self.index.get_object_properties(object_path)
+    }
+
+    /// Read a single channel from the file.
+    ///
+    /// See [`crate::TdmsFile::read_channel`].
+    pub async fn read_channel<D: TdmsStorageType>(
+        &mut self,
+        channel: &ChannelPath,
+        output: &mut [D],
+    ) -> Result<(), TdmsError> {
+        self.read_channel_from(channel, 0, output).await
+    }
+
+    /// Read a single channel from the file starting at a specific sample position.
+    ///
+    /// See [`crate::TdmsFile::read_channel_from`].
+    pub async fn read_channel_from<D: TdmsStorageType>(
+        &mut self,
+        channel: &ChannelPath,
+        start: u64,
+        output: &mut [D],
+    ) -> Result<(), TdmsError> {
+        self.read_channels_from(&[channel], start, &mut [output])
+            .await
+    }
+
+    /// Read multiple channels from the file.
+    ///
+    /// See [`crate::TdmsFile::read_channels`].
+    pub async fn read_channels<D: TdmsStorageType>(
+        &mut self,
+        channels: &[impl AsRef<ChannelPath>],
+        output: &mut [&mut [D]],
+    ) -> Result<(), TdmsError> {
+        self.read_channels_from(channels, 0, output).await
+    }
+
+    /// Read multiple channels from the file starting at a specific sample position.
+    ///
+    /// See [`crate::TdmsFile::read_channels_from`].
+    pub async fn read_channels_from<D: TdmsStorageType>(
+        &mut self,
+        channels: &[impl AsRef<ChannelPath>],
+        start: u64,
+        output: &mut [&mut [D]],
+    ) -> Result<(), TdmsError> {
+        let plan = plan_channels_read(&self.index, channels, start)?;
+        execute_read_plan_async(&self.index, &mut self.file, plan, output).await
+    }
+}
+
+/// Scan the segments in the file to build the index.
+///
+/// This matches the synchronous indexing but loads the lead in and metadata for
+/// each segment before parsing it from memory.
+async fn build_index_async(
+    file: &mut (impl AsyncRead + AsyncSeek + Unpin),
+) -> Result<Index, TdmsError> {
+    let mut index = Index::new();
+
+    //Make sure we are at the beginning.
+    file.seek(SeekFrom::Start(0)).await?;
+
+    loop {
+        match read_segment_async(file).await {
+            Ok(segment) => {
+                let next_segment = index.add_segment(segment)?;
+                if file.seek(SeekFrom::Start(next_segment)).await.is_err() {
+                    break;
+                }
+            }
+            Err(TdmsError::EndOfFile) => break,
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(index)
+}
+
+/// Read the segment header at the current position.
+async fn read_segment_async(file: &mut (impl AsyncRead + Unpin)) -> Result<Segment, TdmsError> {
+    let mut lead_in = [0u8; LEAD_IN_BYTES as usize];
+    match file.read_exact(&mut lead_in[..4]).await {
+        Ok(_) => {}
+        Err(e) if e.kind() == std::io::ErrorKind::UnexpectedEof => {
+            return Err(TdmsError::EndOfFile);
+        }
+        Err(e) => return Err(TdmsError::IoError(e)),
+    }
+    file.read_exact(&mut lead_in[4..]).await?;
+
+    let meta_data_length = Segment::header_length(&lead_in)? - LEAD_IN_BYTES;
+    let mut header = lead_in.to_vec();
+    file.take(meta_data_length).read_to_end(&mut header).await?;
+
+    Segment::read(&mut Cursor::new(header))
+}
+
+/// Execute the read plan, loading each read span asynchronously and decoding it from memory.
+async fn execute_read_plan_async<D: TdmsStorageType>(
+    index: &Index,
+    file: &mut (impl AsyncRead + AsyncSeek + Unpin),
+    plan: Vec<BlockRead>,
+    output: &mut [&mut [D]],
+) -> Result<(), TdmsError> {
+    let mut channel_progress: Vec<ChannelProgress> = output
+        .iter()
+        .map(|out_slice| ChannelProgress::new(out_slice.len()))
+        .collect();
+
+    let blocks = plan
+        .iter()
+        .map(|location| get_block(index, location.data_block))
+        .collect::<Result<Vec<_>, TdmsError>>()?;
+    let spans = plan_read_spans(
+        blocks.iter().map(|block| (block.start, block.length.get())),
+        MAX_SPAN_GAP,
+        MAX_SPAN_BYTES,
+    );
+
+    let mut locations = plan.iter().zip(blocks.iter());
+
+    for span in spans {
+        let span_locations = locations.by_ref().take(span.blocks);
+
+        if span.end - span.start > MAX_SPAN_BYTES {
+            // A single large block. Load just the parts we need so we don't hold it all in memory.
+            for (location, block) in span_locations {
+                let pieces = split_block(block, location, &channel_progress, MAX_SPAN_BYTES)?;
+                read_pieces_async(file, &pieces, output, &mut channel_progress).await?;
+            }
+        } else {
+            let mut buffer = load_async(file, span.start, span.end - span.start).await?;
+            for (location, block) in span_locations {
+                read_block(block, &mut buffer, location, output, &mut channel_progress)?;
+                if all_channels_complete(&channel_progress) {
+                    break;
+                }
+            }
+        }
+
+        if all_channels_complete(&channel_progress) {
+            break;
+        }
+    }
+
+    Ok(())
+}
+
+/// Load and decode the pieces of a block, merging neighbouring pieces into a single read.
+async fn read_pieces_async<D: TdmsStorageType>(
+    file: &mut (impl AsyncRead + AsyncSeek + Unpin),
+    pieces: &[BlockPiece],
+    output: &mut [&mut [D]],
+    channel_progress: &mut [ChannelProgress],
+) -> Result<(), TdmsError> {
+    let spans = plan_read_spans(
+        pieces
+            .iter()
+            .map(|piece| (piece.block.start, piece.block.length.get())),
+        MAX_SPAN_GAP,
+        MAX_SPAN_BYTES,
+    );
+    let mut pieces = pieces.iter();
+
+    for span in spans {
+        let mut buffer = load_async(file, span.start, span.end - span.start).await?;
+        for piece in pieces.by_ref().take(span.blocks) {
+            let needed = piece
+                .location
+                .channel_indexes
+                .iter()
+                .zip(channel_progress.iter())
+                .any(|(plan, progress)| plan.is_some() && !progress.is_complete());
+            if !needed {
+                continue;
+            }
+            read_block(
+                &piece.block,
+                &mut buffer,
+                &piece.location,
+                output,
+                channel_progress,
+            )?;
+        }
+
+        if all_channels_complete(channel_progress) {
+            break;
+        }
+    }
+
+    Ok(())
+}
+
+/// The async equivalent of [`BlockBuffer::load`].
+async fn load_async(
+    file: &mut (impl AsyncRead + AsyncSeek + Unpin),
+    start: u64,
+    length: u64,
+) -> Result<BlockBuffer, TdmsError> {
+    file.seek(SeekFrom::Start(start)).await?;
+    let mut bytes = Vec::new();
+    bytes
+        .try_reserve_exact(length as usize)
+        .map_err(|_| TdmsError::VecAllocationFailed)?;
+    file.take(length).read_to_end(&mut bytes).await?;
+    Ok(BlockBuffer::new(start, bytes))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{DataLayout, TdmsFile};
+
+    fn build_file(layout: DataLayout) -> Vec<u8> {
+        let mut buffer = Cursor::new(Vec::new());
+        let mut file = TdmsFile::new(&mut buffer).unwrap();
+        let channels = [
+            ChannelPath::new("group", "ch1"),
+            ChannelPath::new("group", "ch2"),
+        ];
+        let mut writer = file.writer().unwrap();
+        for block in 0..5 {
+            let values: Vec<f64> = (0..200).map(|i| (block * 1000 + i) as f64).collect();
+            writer.write_channels(&channels, &values, layout).unwrap();
+        }
+        writer
+            .write_properties(
+                &PropertyPath::file(),
+                &[("name", PropertyValue::String("test".to_string()))],
+            )
+            .unwrap();
+        drop(writer);
+        drop(file);
+        buffer.into_inner()
+    }
+
+    fn block_on<T>(future: impl std::future::Future<Output = T>) -> T {
+        tokio::runtime::Builder::new_current_thread()
+            .build()
+            .unwrap()
+            .block_on(future)
+    }
+
+    #[test]
+    fn async_index_matches_sync_index() {
+        let bytes = build_file(DataLayout::Interleaved);
+        let sync_file = TdmsFile::new(Cursor::new(bytes.clone())).unwrap();
+        let async_file = block_on(AsyncTdmsFile::new(Cursor::new(bytes))).unwrap();
+        assert_eq!(
+            async_file.index.indexed_length(),
+            sync_file.index.indexed_length()
+        );
+        for channel in ["ch1", "ch2"] {
+            let channel = ChannelPath::new("group", channel);
+            assert_eq!(
+                async_file.index.get_channel_data_positions(&channel),
+                sync_file.index.get_channel_data_positions(&channel)
+            );
+        }
+    }
+
+    #[test]
+    fn async_reads_match_sync_reads() {
+        for layout in [DataLayout::Contigious, DataLayout::Interleaved] {
+            let bytes = build_file(layout);
+            let channels = [
+                ChannelPath::new("group", "ch2"),
+                ChannelPath::new("group", "ch1"),
+            ];
+
+            let mut sync_file = TdmsFile::new(Cursor::new(bytes.clone())).unwrap();
+            let mut expected1 = vec![0.0f64; 300];
+            let mut expected2 = vec![0.0f64; 300];
+            sync_file
+                .read_channels_from(&channels, 50, &mut [&mut expected1, &mut expected2])
+                .unwrap();
+
+            let mut async_file = block_on(AsyncTdmsFile::new(Cursor::new(bytes))).unwrap();
+            let mut output1 = vec![0.0f64; 300];
+            let mut output2 = vec![0.0f64; 300];
+            block_on(async_file.read_channels_from(
+                &channels,
+                50,
+                &mut [&mut output1, &mut output2],
+            ))
+            .unwrap();
+
+            assert_eq!(output1, expected1);
+            assert_eq!(output2, expected2);
+        }
+    }
+
+    /// Counts the bytes read from the inner stream.
+    struct CountingReader {
+        inner: Cursor<Vec<u8>>,
+        bytes_read: usize,
+    }
+
+    impl AsyncRead for CountingReader {
+        fn poll_read(
+            mut self: std::pin::Pin<&mut Self>,
+            cx: &mut std::task::Context<'_>,
+            buf: &mut tokio::io::ReadBuf<'_>,
+        ) -> std::task::Poll<std::io::Result<()>> {
+            let filled = buf.filled().len();
+            let result = std::pin::Pin::new(&mut self.inner).poll_read(cx, buf);
+            self.bytes_read += buf.filled().len() - filled;
+            result
+        }
+    }
+
+    impl AsyncSeek for CountingReader {
+        fn start_seek(
+            mut self: std::pin::Pin<&mut Self>,
+            position: SeekFrom,
+        ) -> std::io::Result<()> {
+            std::pin::Pin::new(&mut self.inner).start_seek(position)
+        }
+
+        fn poll_complete(
+            mut self: std::pin::Pin<&mut Self>,
+            cx: &mut std::task::Context<'_>,
+        ) -> std::task::Poll<std::io::Result<u64>> {
+            std::pin::Pin::new(&mut self.inner).poll_complete(cx)
+        }
+    }
+
+    #[test]
+    fn large_blocks_are_read_in_pieces() {
+        for layout in [DataLayout::Contigious, DataLayout::Interleaved] {
+            // A single block of 2 channels which is larger than a read span.
+            let mut file = TdmsFile::new(Cursor::new(Vec::new())).unwrap();
+            let values: Vec<f64> = (0..200_000).map(|i| i as f64).collect();
+            let channels = [
+                ChannelPath::new("group", "ch1"),
+                ChannelPath::new("group", "ch2"),
+            ];
+            file.writer()
+                .unwrap()
+                .write_channels(&channels, &values, layout)
+                .unwrap();
+            let block_length = file.index.get_data_block(0).unwrap().length.get();
+            assert!(block_length > MAX_SPAN_BYTES);
+
+            let mut expected = vec![0.0f64; 1000];
+            file.read_channel_from(&channels[1], 50_000, &mut expected)
+                .unwrap();
+
+            let reader = CountingReader {
+                inner: Cursor::new(file.file.into_inner()),
+                bytes_read: 0,
+            };
+            let mut async_file = block_on(AsyncTdmsFile::new(reader)).unwrap();
+            async_file.file.bytes_read = 0;
+            let mut output = vec![0.0f64; 1000];
+            block_on(async_file.read_channel_from(&channels[1], 50_000, &mut output)).unwrap();
+
+            assert_eq!(output, expected);
+            // Only the piece holding the samples is loaded, not the whole block.
+            assert!((async_file.file.bytes_read as u64) <= MAX_SPAN_BYTES);
+        }
+    }
+
+    #[test]
+    fn async_read_property() {
+        let bytes = build_file(DataLayout::Contigious);
+        let file = block_on(AsyncTdmsFile::new(Cursor::new(bytes))).unwrap();
+        assert_eq!(
+            file.read_property(&PropertyPath::file(), "name").unwrap(),
+            Some(&PropertyValue::String("test".to_string()))
+        );
+    }
+
+    #[test]
+    fn empty_file_has_no_channels() {
+        let file = block_on(AsyncTdmsFile::new(Cursor::new(Vec::new()))).unwrap();
+        assert_eq!(file.channel_length(&ChannelPath::new("group", "ch1")), None);
+    }
+}
@@ -0,0 +1,385 @@
+//! Async writing of TDMS files using tokio.
+//!
+//! Each segment is serialised into memory with the same code as the synchronous
+//! writers and then written to the stream. The index is only updated once the
+//! write has completed.
+
+use std::io::SeekFrom;
+use std::path::Path;
+
+use tokio::io::{AsyncRead, AsyncSeek, AsyncSeekExt, AsyncWrite, AsyncWriteExt};
+
+use super::AsyncTdmsFile;
+use super::file_writer::{write_channels_segment, write_properties_segment};
+use crate::error::TdmsError;
+use crate::index::Index;
+use crate::io::data_types::TdmsStorageType;
+use crate::io::writer::{BigEndianWriter, LittleEndianWriter, TdmsWriter};
+use crate::meta_data::Segment;
+use crate::{ChannelPath, DataLayout, PropertyPath, PropertyValue};
+
+/// A TDMS writer using tokio async IO.
+///
+/// This owns the stream and index like [`crate::TdmsWriterHandle`].
+///
+/// Create it with [`Self::create`] or [`Self::new`] for a new stream, or with
+/// [`AsyncTdmsFile::into_writer`] to append to an existing file.
+///
+/// If a write fails or is cancelled the stream may contain part of a segment.
+/// The index won't include it and any later writes return [`TdmsError::WriterPoisoned`],
+/// so the writer should be discarded.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use tedium::{AsyncTdmsFileWriter, ChannelPath, DataLayout};
+///
+/// # async fn example() -> Result<(), tedium::TdmsError> {
+/// let mut writer = AsyncTdmsFileWriter::create("data.tdms").await?;
+/// writer
+///     .write_channels(
+///         &[ChannelPath::new("group", "channel")],
+///         &[1.0, 2.0, 3.0],
+///         DataLayout::Contigious,
+///     )
+///     .await?;
+/// writer.sync().await?;
+/// # Ok(())
+/// # }
+/// ```
+pub struct AsyncTdmsFileWriter<
+    F: AsyncWrite + AsyncSeek + Unpin,
+    W: TdmsWriter<Vec<u8>> = LittleEndianWriter<Vec<u8>>,
+> {
+    index: Index,
+    file: F,
+    /// Reused between segments to avoid allocating each time.
+    buffer: Vec<u8>,
+    /// Set while a segment is being written so a failed or cancelled write is remembered.
+    poisoned: bool,
+    _writer: std::marker::PhantomData<W>,
+}
+
+impl AsyncTdmsFileWriter<tokio::fs::File> {
+    /// Create a new file at the path. This will replace any existing file.
+    pub async fn create(path: impl AsRef<Path>) -> Result<Self, TdmsError> {
+        let file = tokio::fs::File::create(path).await?;
+        Ok(Self::new(file))
+    }
+}
+
+impl<F: AsyncWrite + AsyncSeek + Unpin> AsyncTdmsFileWriter<F> {
+    /// Create a writer for a new TDMS stream.
+    ///
+    /// The stream is assumed to be empty so the index starts fresh.
+    pub fn new(file: F) -> Self {
+        Self::with_index(Index::new(), file)
+    }
+}
+
+impl<F: AsyncWrite + AsyncSeek + Unpin> AsyncTdmsFileWriter<F, BigEndianWriter<Vec<u8>>> {
+    /// Create a writer for a new TDMS stream which writes big endian segments.
+    ///
+    /// See [`AsyncTdmsFileWriter::new`].
+    pub fn new_big_endian(file: F) -> Self {
+        Self::with_index(Index::new(), file)
+    }
+}
+
+impl<F: AsyncWrite + AsyncSeek + Unpin, W: TdmsWriter<Vec<u8>>> AsyncTdmsFileWriter<F, W> {
+    /// The stream must be positioned at the end of the file described by the index.
+    fn with_index(index: Index, file: F) -> Self {
+        Self {
+            index,
+            file,
+            buffer: Vec::new(),
+            poisoned: false,
+            _writer: std::marker::PhantomData,
+        }
+    }
+
+    /// Write the data to the given channels.
+    ///
+    /// See [`crate::TdmsFileWriter::write_channels`] for details of the layout.
+    pub async fn write_channels<D: TdmsStorageType, C: AsRef<ChannelPath>>(
+        &mut self,
+        channels: &[C],
+        values: &[D],
+        layout: DataLayout,
+    ) -> Result<(), TdmsError> {
+        self.check_poisoned()?;
+        let mut writer = self.segment_writer();
+        let segment = write_channels_segment(&self.index, &mut writer, channels, values, layout)?;
+        self.write_segment(writer, segment).await
+    }
+
+    /// Write the properties to the given path.
+    /// This will overwrite any existing properties.
+    pub async fn write_properties(
+        &mut self,
+        path: &PropertyPath,
+        properties: &[(&str, PropertyValue)],
+    ) -> Result<(), TdmsError> {
+        self.check_poisoned()?;
+        let mut writer = self.segment_writer();
+        let segment = write_properties_segment(&mut writer, path, properties)?;
+        self.write_segment(writer, segment).await
+    }
+
+    /// Flushes any buffered data to the stream.
+    ///
+    /// For a [`tokio::fs::File`] this doesn't guarantee the data has reached the disk.
+    pub async fn sync(&mut self) -> Result<(), TdmsError> {
+        self.file.flush().await?;
+        Ok(())
+    }
+
+    /// Flush any buffered data and return the underlying stream.
+    pub async fn into_inner(mut self) -> Result<F, TdmsError> {
+        self.sync().await?;
+        Ok(self.file)
+    }
+
+    fn check_poisoned(&self) -> Result<(), TdmsError> {
+        if self.poisoned {
+            return Err(TdmsError::WriterPoisoned);
+        }
+        Ok(())
+    }
+
+    fn segment_writer(&mut self) -> W {
+        let mut buffer = std::mem::take(&mut self.buffer);
+        buffer.clear();
+        W::from_writer(buffer)
+    }
+
+    /// Write the serialised segment to the stream and then add it to the index.
+    ///
+    /// The writer stays poisoned unless the write completes, which also covers the
+    /// future being dropped part way through.
+    async fn write_segment(&mut self, writer: W, segment: Segment) -> Result<(), TdmsError> {
+        let buffer = writer.into_inner()?;
+        self.poisoned = true;
+        self.file.write_all(&buffer).await?;
+        self.poisoned = false;
+        self.buffer = buffer;
+        self.index.add_segment(segment)?;
+        Ok(())
+    }
+}
+
+impl<F: AsyncRead + AsyncWrite + AsyncSeek + Unpin, W: TdmsWriter<Vec<u8>>>
+    AsyncTdmsFileWriter<F, W>
+{
+    /// Finish writing and return an [`AsyncTdmsFile`] that can be used to read the data.
+    ///
+    /// This flushes any buffered data to the stream first.
+    pub async fn into_file(mut self) -> Result<AsyncTdmsFile<F>, TdmsError> {
+        self.sync().await?;
+        Ok(AsyncTdmsFile::from_parts(self.index, self.file))
+    }
+}
+
+impl<F: AsyncRead + AsyncWrite + AsyncSeek + Unpin> AsyncTdmsFile<F> {
+    /// Convert the file into a writer which appends to it.
+    ///
+    /// See [`crate::TdmsFile::into_writer`].
+    pub async fn into_writer(self) -> Result<AsyncTdmsFileWriter<F>, TdmsError> {
+        self.into_writer_with().await
+    }
+
+    /// Convert the file into a writer which appends big endian segments to it.
+    ///
+    /// See [`crate::TdmsFile::into_big_endian_writer`].
+    pub async fn into_big_endian_writer(
+        self,
+    ) -> Result<AsyncTdmsFileWriter<F, BigEndianWriter<Vec<u8>>>, TdmsError> {
+        self.into_writer_with().await
+    }
+
+    async fn into_writer_with<W: TdmsWriter<Vec<u8>>>(
+        self,
+    ) -> Result<AsyncTdmsFileWriter<F, W>, TdmsError> {
+        let (index, mut file) = self.into_parts();
+        //make sure we are at the end.
+        file.seek(SeekFrom::End(0)).await?;
+        Ok(AsyncTdmsFileWriter::with_index(index, file))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use super::*;
+    use crate::TdmsWriterHandle;
+
+    fn block_on<T>(future: impl std::future::Future<Output = T>) -> T {
+        tokio::runtime::Builder::new_current_thread()
+            .build()
+            .unwrap()
+            .block_on(future)
+    }
+
+    fn channels() -> [ChannelPath; 2] {
+        [
+            ChannelPath::new("group", "ch1"),
+            ChannelPath::new("group", "ch2"),
+        ]
+    }
+
+    fn properties() -> [(&'static str, PropertyValue); 1] {
+        [("unit", PropertyValue::String("V".to_string()))]
+    }
+
+    fn values(block: usize) -> Vec<f64> {
+        (0..10).map(|i| (block * 10 + i) as f64).collect()
+    }
+
+    fn write_sync<W: TdmsWriter<Vec<u8>>>(mut writer: TdmsWriterHandle<Vec<u8>, W>) -> Vec<u8> {
+        writer
+            .write_properties(&PropertyPath::group("group"), &properties())
+            .unwrap();
+        for block in 0..3 {
+            writer
+                .write_channels(&channels(), &values(block), DataLayout::Interleaved)
+                .unwrap();
+        }
+        writer.into_inner().unwrap()
+    }
+
+    fn write_async<W: TdmsWriter<Vec<u8>>>(
+        mut writer: AsyncTdmsFileWriter<Cursor<Vec<u8>>, W>,
+    ) -> Vec<u8> {
+        block_on(async {
+            writer
+                .write_properties(&PropertyPath::group("group"), &properties())
+                .await
+                .unwrap();
+            for block in 0..3 {
+                writer
+                    .write_channels(&channels(), &values(block), DataLayout::Interleaved)
+                    .await
+                    .unwrap();
+            }
+            writer.into_inner().await.unwrap().into_inner()
+        })
+    }
+
+    #[test]
+    fn async_writer_matches_sync_writer() {
+        let expected = write_sync(TdmsWriterHandle::from_writer(Vec::new()));
+        let written = write_async(AsyncTdmsFileWriter::new(Cursor::new(Vec::new())));
+        assert_eq!(written, expected);
+    }
+
+    #[test]
+    fn async_big_endian_writer_matches_sync_writer() {
+        let expected = write_sync(TdmsWriterHandle::from_big_endian_writer(Vec::new()));
+        let written = write_async(AsyncTdmsFileWriter::new_big_endian(Cursor::new(Vec::new())));
+        assert_eq!(written, expected);
+    }
+
+    #[test]
+    fn append_and_read_back() {
+        let bytes = write_async(AsyncTdmsFileWriter::new(Cursor::new(Vec::new())));
+
+        block_on(async {
+            let file = AsyncTdmsFile::new(Cursor::new(bytes)).await.unwrap();
+            let mut writer = file.into_writer().await.unwrap();
+            writer
+                .write_channels(&channels(), &values(3), DataLayout::Interleaved)
+                .await
+                .unwrap();
+            let mut file = writer.into_file().await.unwrap();
+
+            let mut output = vec![0.0f64; 20];
+            file.read_channel(&ChannelPath::new("group", "ch2"), &mut output)
+                .await
+                .unwrap();
+            let expected: Vec<f64> = (0..4)
+                .flat_map(|block| values(block).into_iter().skip(1).step_by(2))
+                .collect();
+            assert_eq!(output, expected);
+        });
+    }
+
+    /// Fails every write once `limit` bytes have been written.
+    struct FailingWriter {
+        inner: Cursor<Vec<u8>>,
+        limit: usize,
+    }
+
+    impl AsyncWrite for FailingWriter {
+        fn poll_write(
+            mut self: std::pin::Pin<&mut Self>,
+            cx: &mut std::task::Context<'_>,
+            buf: &[u8],
+        ) -> std::task::Poll<std::io::Result<usize>> {
+            let available = self.limit.saturating_sub(self.inner.get_ref().len());
+            if available == 0 {
+                return std::task::Poll::Ready(Err(std::io::Error::other("disk full")));
+            }
+            let length = available.min(buf.len());
+            std::pin::Pin::new(&mut self.inner).poll_write(cx, &buf[..length])
+        }
+
+        fn poll_flush(
+            mut self: std::pin::Pin<&mut Self>,
+            cx: &mut std::task::Context<'_>,
+        ) -> std::task::Poll<std::io::Result<()>> {
+            std::pin::Pin::new(&mut self.inner).poll_flush(cx)
+        }
+
+        fn poll_shutdown(
+            mut self: std::pin::Pin<&mut Self>,
+            cx: &mut std::task::Context<'_>,
+        ) -> std::task::Poll<std::io::Result<()>> {
+            std::pin::Pin::new(&mut self.inner).poll_shutdown(cx)
+        }
+    }
+
+    impl AsyncSeek for FailingWriter {
+        fn start_seek(
+            mut self: std::pin::Pin<&mut Self>,
+            position: SeekFrom,
+        ) -> std::io::Result<()> {
+            std::pin::Pin::new(&mut self.inner).start_seek(position)
+        }
+
+        fn poll_complete(
+            mut self: std::pin::Pin<&mut Self>,
+            cx: &mut std::task::Context<'_>,
+        ) -> std::task::Poll<std::io::Result<u64>> {
+            std::pin::Pin::new(&mut self.inner).poll_complete(cx)
+        }
+    }
+
+    #[test]
+    fn failed_write_poisons_writer() {
+        let file = FailingWriter {
+            inner: Cursor::new(Vec::new()),
+            limit: 100,
+        };
+        let mut writer = AsyncTdmsFileWriter::new(file);
+
+        block_on(async {
+            let result = writer
+                .write_channels(&channels(), &values(0), DataLayout::Interleaved)
+                .await;
+            assert!(matches!(result, Err(TdmsError::IoError(_))));
+
+            // Even with room to write, the stream now holds part of a segment.
+            writer.file.limit = usize::MAX;
+            let result = writer
+                .write_properties(&PropertyPath::group("group"), &properties())
+                .await;
+            assert!(matches!(result, Err(TdmsError::WriterPoisoned)));
+            let result = writer
+                .write_channels(&channels(), &values(1), DataLayout::Interleaved)
+                .await;
+            assert!(matches!(result, Err(TdmsError::WriterPoisoned)));
+        });
+        assert_eq!(writer.file.inner.get_ref().len(), 100);
+    }
+}
@@ -0,0 +1,289 @@
+//! A background writer which moves the disk I/O onto a dedicated thread.
+//!
+//! Producers push data into a bounded queue and the writer thread drains it
+//! into a [`TdmsWriterHandle`]. The bound on the queue provides back-pressure
+//! while [`WriteQueue::try_write_channels`] lets real-time producers avoid
+//! blocking at all.
+
+use std::io::Write;
+use std::sync::mpsc::{Receiver, SyncSender, TrySendError, sync_channel};
+use std::sync::{Arc, OnceLock};
+use std::thread::JoinHandle;
+
+use super::TdmsWriterHandle;
+use crate::error::TdmsError;
+use crate::io::data_types::TdmsStorageType;
+use crate::io::writer::{LittleEndianWriter, TdmsWriter};
+use crate::{ChannelPath, DataLayout, PropertyPath, PropertyValue};
+
+type WriteJob<F, W> =
+    Box<dyn FnOnce(&mut TdmsWriterHandle<F, W>) -> Result<(), TdmsError> + Send + 'static>;
+
+enum Command<F: Write, W: TdmsWriter<F>> {
+    Write(WriteJob<F, W>),
+    Flush(SyncSender<Result<(), TdmsError>>),
+    Stop,
+}
+
+/// Set when the writer thread stops, with the error that stopped it if there was one.
+type StopReason = OnceLock<Option<Arc<TdmsError>>>;
+
+/// The error to return to a producer once the writer thread has stopped.
+fn stopped_error(stopped: &StopReason) -> TdmsError {
+    match stopped.get() {
+        Some(Some(error)) => TdmsError::BackgroundWriterFailed(error.clone()),
+        _ => TdmsError::BackgroundWriterStopped,
+    }
+}
+
+/// Runs a [`TdmsWriterHandle`] on a dedicated thread.
+///
+/// Use [`Self::queue`] to get a handle that producers can push data through.
+/// Any error on the writer thread stops it. Every call on the queues after that,
+/// any pending flush and [`Self::finish`] return [`TdmsError::BackgroundWriterFailed`]
+/// with the error that stopped it.
+///
+/// # Example
+///
+/// ```rust
+/// use tedium::{BackgroundWriter, TdmsFile, ChannelPath, DataLayout};
+///
+/// let fake_file = std::io::Cursor::new(vec![]);
+/// let writer = TdmsFile::new(fake_file).unwrap().into_writer().unwrap();
+/// let background = BackgroundWriter::spawn(writer, 16).unwrap();
+///
+/// let queue = background.queue();
+/// queue.write_channels(
+///     vec![ChannelPath::new("group", "channel")],
+///     vec![1.0, 2.0, 3.0],
+///     DataLayout::Contigious,
+/// ).unwrap();
+/// queue.flush().unwrap().wait().unwrap();
+///
+/// let mut file = background.finish().unwrap().into_file().unwrap();
+/// file.read_channel(&ChannelPath::new("group", "channel"), &mut [0.0f64; 3]).unwrap();
+/// ```
+pub struct BackgroundWriter<F: Write, W: TdmsWriter<F> = LittleEndianWriter<F>> {
+    queue: WriteQueue<F, W>,
+    thread: JoinHandle<Result<TdmsWriterHandle<F, W>, TdmsError>>,
+}
+
+impl<F, W> BackgroundWriter<F, W>
+where
+    F: Write + Send + 'static,
+    W: TdmsWriter<F> + Send + 'static,
+{
+    /// Start the writer thread.
+    ///
+    /// `queue_depth` is the number of writes that can be queued before producers
+    /// using the blocking methods on [`WriteQueue`] will wait for the disk.
+    pub fn spawn(writer: TdmsWriterHandle<F, W>, queue_depth: usize) -> Result<Self, TdmsError> {
+        let (sender, receiver) = sync_channel(queue_depth);
+        let stopped = Arc::new(StopReason::new());
+        let thread_stopped = stopped.clone();
+
+        let thread = std::thread::Builder::new()
+            .name("tedium-writer".to_string())
+            .spawn(move || run_writer(writer, receiver, thread_stopped))?;
+
+        Ok(Self {
+            queue: WriteQueue { sender, stopped },
+            thread,
+        })
+    }
+
+    /// Get a queue for pushing writes to this writer.
+    ///
+    /// The queue can be cloned to share between multiple producer threads.
+    pub fn queue(&self) -> WriteQueue<F, W> {
+        self.queue.clone()
+    }
+
+    /// Stop the writer thread once all writes queued so far are complete.
+    ///
+    /// Returns the writer so it can be converted back into a file or
+    /// [`TdmsError::BackgroundWriterFailed`] with the error that stopped the writer thread.
+    pub fn finish(self) -> Result<TdmsWriterHandle<F, W>, TdmsError> {
+        // If this fails the thread has already stopped and we get the reason from the join.
+        let _ = self.queue.sender.send(Command::Stop);
+        self.thread
+            .join()
+            .map_err(|_| TdmsError::BackgroundWriterStopped)?
+    }
+}
+
+fn run_writer<F: Write, W: TdmsWriter<F>>(
+    mut writer: TdmsWriterHandle<F, W>,
+    receiver: Receiver<Command<F, W>>,
+    stopped: Arc<StopReason>,
+) -> Result<TdmsWriterHandle<F, W>, TdmsError> {
+    // Record the error before anything is dropped so producers waiting on a
+    // flush see the cause.
+    let fail = |error: TdmsError| {
+        let error = Arc::new(error);
+        let _ = stopped.set(Some(error.clone()));
+        TdmsError::BackgroundWriterFailed(error)
+    };
+
+    let result = loop {
+        let Ok(command) = receiver.recv() else {
+            // All queues have been dropped.
+            break Ok(());
+        };
+
+        match command {
+            Command::Write(job) => {
+                if let Err(e) = job(&mut writer) {
+                    break Err(fail(e));
+                }
+            }
+            Command::Flush(reply) => {
+                if let Err(e) = writer.sync() {
+                    break Err(fail(e));
+                }
+                // The caller may have dropped the handle without waiting.
+                let _ = reply.send(Ok(()));
+            }
+            Command::Stop => break Ok(()),
+        }
+    };
+
+    result?;
+    writer.sync().map_err(fail)?;
+    let _ = stopped.set(None);
+    Ok(writer)
+}
+
+/// A handle for pushing writes to a [`BackgroundWriter`].
+///
+/// Data is passed by value so it can be moved to the writer thread.
+pub struct WriteQueue<F: Write, W: TdmsWriter<F> = LittleEndianWriter<F>> {
+    sender: SyncSender<Command<F, W>>,
+    stopped: Arc<StopReason>,
+}
+
+impl<F: Write, W: TdmsWriter<F>> Clone for WriteQueue<F, W> {
+    fn clone(&self) -> Self {
+        Self {
+            sender: self.sender.clone(),
+            stopped: self.stopped.clone(),
+        }
+    }
+}
+
+impl<F: Write, W: TdmsWriter<F>> WriteQueue<F, W> {
+    /// Queue data to write to the given channels.
+    ///
+    /// This will wait if the queue is full.
+    /// See [`crate::TdmsFileWriter::write_channels`] for details of the layout.
+    pub fn write_channels<D: TdmsStorageType + Send>(
+        &self,
+        channels: Vec<ChannelPath>,
+        values: Vec<D>,
+        layout: DataLayout,
+    ) -> Result<(), TdmsError> {
+        self.send(channels_job(channels, values, layout))
+    }
+
+    /// Queue data to write to the given channels without waiting.
+    ///
+    /// Returns [`TdmsError::WriteQueueFull`] if the queue is full. In this case the data
+    /// is not written.
+    pub fn try_write_channels<D: TdmsStorageType + Send>(
+        &self,
+        channels: Vec<ChannelPath>,
+        values: Vec<D>,
+        layout: DataLayout,
+    ) -> Result<(), TdmsError> {
+        self.check_running()?;
+        match self
+            .sender
+            .try_send(Command::Write(channels_job(channels, values, layout)))
+        {
+            Ok(()) => Ok(()),
+            Err(TrySendError::Full(_)) => Err(TdmsError::WriteQueueFull),
+            Err(TrySendError::Disconnected(_)) => Err(stopped_error(&self.stopped)),
+        }
+    }
+
+    /// Queue properties to write to the given path.
+    ///
+    /// This will wait if the queue is full.
+    pub fn write_properties(
+        &self,
+        path: PropertyPath,
+        properties: Vec<(String, PropertyValue)>,
+    ) -> Result<(), TdmsError> {
+        self.send(Box::new(move |writer| {
+            let properties: Vec<(&str, PropertyValue)> = properties
+                .iter()
+                .map(|(name, value)| (name.as_str(), value.clone()))
+                .collect();
+            writer.write_properties(&path, &properties)
+        }))
+    }
+
+    /// Request the writer flushes all previously queued data to the OS.
+    ///
+    /// This does not make the data durable. Use a [`crate::SyncPolicy`] on the
+    /// writer if it must reach the disk.
+    ///
+    /// Call [`FlushHandle::wait`] on the result to wait for it to complete.
+    pub fn flush(&self) -> Result<FlushHandle, TdmsError> {
+        self.check_running()?;
+        let (reply, receiver) = sync_channel(1);
+        self.sender
+            .send(Command::Flush(reply))
+            .map_err(|_| stopped_error(&self.stopped))?;
+        Ok(FlushHandle {
+            receiver,
+            stopped: self.stopped.clone(),
+        })
+    }
+
+    /// Check if the writer thread has stopped.
+    pub fn is_stopped(&self) -> bool {
+        self.stopped.get().is_some()
+    }
+
+    fn check_running(&self) -> Result<(), TdmsError> {
+        if self.is_stopped() {
+            Err(stopped_error(&self.stopped))
+        } else {
+            Ok(())
+        }
+    }
+
+    fn send(&self, job: WriteJob<F, W>) -> Result<(), TdmsError> {
+        self.check_running()?;
+        self.sender
+            .send(Command::Write(job))
+            .map_err(|_| stopped_error(&self.stopped))
+    }
+}
+
+fn channels_job<F: Write, W: TdmsWriter<F>, D: TdmsStorageType + Send>(
+    channels: Vec<ChannelPath>,
+    values: Vec<D>,
+    layout: DataLayout,
+) -> WriteJob<F, W> {
+    Box::new(move |writer| writer.write_channels(&channels, &values, layout))
+}
+
+/// A pending flush of a [`BackgroundWriter`].
+pub struct FlushHandle {
+    receiver: Receiver<Result<(), TdmsError>>,
+    stopped: Arc<StopReason>,
+}
+
+impl FlushHandle {
+    /// Wait for all data queued before the flush to be flushed to the OS.
+    ///
+    /// Returns [`TdmsError::BackgroundWriterFailed`] with the cause if an error
+    /// stopped the writer first.
+    pub fn wait(self) -> Result<(), TdmsError> {
+        self.receiver
+            .recv()
+            .map_err(|_| stopped_error(&self.stopped))?
+    }
+}
@@ -0,0 +1,181 @@
+//! A bounded cache of channel data.
+//!
+//! Interactive use tends to read the same regions repeatedly so we can keep
+//! the bytes for each channel in a data block and decode them from memory
+//! instead of reading the file again.
+//!
+//! The bytes are kept in the file byte order so the cache works for any
+//! [`crate::TdmsStorageType`] without needing to clone the decoded values.
+//! This means a hit saves the read but the bytes are decoded again each time.
+
+use std::collections::{BTreeMap, HashMap};
+
+/// Identifies the data for a channel in a data block.
+type CacheKey = (usize, usize);
+
+/// Statistics for the block cache.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct BlockCacheStats {
+    /// The number of channel blocks served from the cache.
+    pub hits: u64,
+    /// The number of channel blocks read from the file.
+    pub misses: u64,
+    /// The number of channel blocks currently cached.
+    pub entries: usize,
+    /// The size of the cached data in bytes.
+    pub bytes: usize,
+    /// The maximum size of the cached data in bytes.
+    pub max_bytes: usize,
+}
+
+struct CacheEntry {
+    /// The channel values as stored in the file.
+    values: Vec<u8>,
+    last_used: u64,
+}
+
+/// A least recently used cache of channel data keyed by data block and channel index.
+pub(super) struct BlockCache {
+    entries: HashMap<CacheKey, CacheEntry>,
+    /// The keys ordered by when they were last used.
+    usage: BTreeMap<u64, CacheKey>,
+    next_use: u64,
+    bytes: usize,
+    max_bytes: usize,
+    hits: u64,
+    misses: u64,
+}
+
+impl std::fmt::Debug for BlockCache {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("BlockCache")
+            .field("stats", &self.stats())
+            .finish()
+    }
+}
+
+impl BlockCache {
+    pub fn new(max_bytes: usize) -> Self {
+        Self {
+            entries: HashMap::new(),
+            usage: BTreeMap::new(),
+            next_use: 0,
+            bytes: 0,
+            max_bytes,
+            hits: 0,
+            misses: 0,
+        }
+    }
+
+    pub fn stats(&self) -> BlockCacheStats {
+        BlockCacheStats {
+            hits: self.hits,
+            misses: self.misses,
+            entries: self.entries.len(),
+            bytes: self.bytes,
+            max_bytes: self.max_bytes,
+        }
+    }
+
+    pub fn max_bytes(&self) -> usize {
+        self.max_bytes
+    }
+
+    /// Get the bytes for the channel block, marking them as recently used.
+    pub fn get(&mut self, key: CacheKey) -> Option<&[u8]> {
+        let next_use = self.next_use;
+        let Some(entry) = self.entries.get_mut(&key) else {
+            self.misses += 1;
+            return None;
+        };
+        self.hits += 1;
+        self.next_use += 1;
+        self.usage.remove(&entry.last_used);
+        self.usage.insert(next_use, key);
+        entry.last_used = next_use;
+        Some(&entry.values[..])
+    }
+
+    /// Add the bytes for the channel block, evicting the least recently used values to fit.
+    ///
+    /// Values larger than the whole cache are not stored.
+    pub fn insert(&mut self, key: CacheKey, values: Vec<u8>) {
+        let bytes = values.len();
+        self.remove(key);
+        if bytes > self.max_bytes {
+            return;
+        }
+        while self.bytes + bytes > self.max_bytes {
+            let Some((_, oldest)) = self.usage.pop_first() else {
+                break;
+            };
+            if let Some(entry) = self.entries.remove(&oldest) {
+                self.bytes -= entry.values.len();
+            }
+        }
+
+        let last_used = self.next_use;
+        self.next_use += 1;
+        self.usage.insert(last_used, key);
+        self.bytes += bytes;
+        self.entries.insert(key, CacheEntry { values, last_used });
+    }
+
+    fn remove(&mut self, key: CacheKey) {
+        if let Some(entry) = self.entries.remove(&key) {
+            self.usage.remove(&entry.last_used);
+            self.bytes -= entry.values.len();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hit_and_miss_counted() {
+        let mut cache = BlockCache::new(1024);
+        assert!(cache.get((0, 0)).is_none());
+        cache.insert((0, 0), vec![1u8, 2]);
+        assert_eq!(cache.get((0, 0)), Some(&[1, 2][..]));
+
+        let stats = cache.stats();
+        assert_eq!(stats.hits, 1);
+        assert_eq!(stats.misses, 1);
+        assert_eq!(stats.entries, 1);
+        assert_eq!(stats.bytes, 2);
+    }
+
+    #[test]
+    fn evicts_least_recently_used() {
+        let mut cache = BlockCache::new(32);
+        cache.insert((0, 0), vec![0u8; 16]);
+        cache.insert((1, 0), vec![0u8; 16]);
+        // Use the first so the second is the oldest.
+        cache.get((0, 0)).unwrap();
+        cache.insert((2, 0), vec![0u8; 16]);
+
+        assert!(cache.get((0, 0)).is_some());
+        assert!(cache.get((1, 0)).is_none());
+        assert!(cache.get((2, 0)).is_some());
+        assert_eq!(cache.stats().bytes, 32);
+    }
+
+    #[test]
+    fn oversized_values_not_cached() {
+        let mut cache = BlockCache::new(8);
+        cache.insert((0, 0), vec![0u8; 16]);
+        assert_eq!(cache.stats().entries, 0);
+        assert_eq!(cache.stats().bytes, 0);
+    }
+
+    #[test]
+    fn replacing_entry_updates_size() {
+        let mut cache = BlockCache::new(64);
+        cache.insert((0, 0), vec![0u8; 32]);
+        cache.insert((0, 0), vec![0u8; 16]);
+        assert_eq!(cache.stats().entries, 1);
+        assert_eq!(cache.stats().bytes, 16);
+    }
+}
@@ -0,0 +1,324 @@
+//! Splitting large data blocks into pieces which can be loaded on their own.
+//!
+//! The async and parallel readers load raw data into memory before decoding it.
+//! Splitting the blocks keeps the memory used by a read bounded however large
+//! the blocks in the file are.
+
+use std::num::NonZeroU64;
+use std::ops::Range;
+
+use super::channel_reader::{BlockRead, ChannelProgress, ChannelReadPlan};
+use crate::error::TdmsError;
+use crate::meta_data::RawDataMeta;
+use crate::paths::ChannelPath;
+use crate::raw_data::{ChunkSize, DataBlock, DataLayout};
+
+/// A part of a data block which can be loaded and decoded on its own.
+#[derive(Clone, Debug)]
+pub(super) struct BlockPiece {
+    pub(super) block: DataBlock,
+    pub(super) location: BlockRead,
+}
+
+/// Split a large block into pieces of at most `max_bytes` so it can be loaded a piece at a time.
+///
+/// Only pieces holding samples the channels still need are returned. Contiguous blocks
+/// are split into groups of whole chunks, or into runs of each requested channel when a
+/// single chunk is too large. Interleaved blocks are split into runs of rows.
+///
+/// Blocks with variable sized data such as strings can't be split and are returned whole.
+pub(super) fn split_block(
+    block: &DataBlock,
+    location: &BlockRead,
+    channel_progress: &[ChannelProgress],
+    max_bytes: u64,
+) -> Result<Vec<BlockPiece>, TdmsError> {
+    let whole = || {
+        vec![BlockPiece {
+            block: block.clone(),
+            location: location.clone(),
+        }]
+    };
+    let ChunkSize::Fixed(chunk_size) = block.chunk_size()? else {
+        return Ok(whole());
+    };
+    if chunk_size == 0 || block.channels.iter().any(|c| c.data_type.size() == 0) {
+        return Ok(whole());
+    }
+    let chunks = block.number_of_chunks()? as u64;
+
+    // The channel index and range of samples still needed for each output.
+    let wanted: Vec<Option<(usize, Range<u64>)>> = location
+        .channel_indexes
+        .iter()
+        .zip(channel_progress)
+        .map(|(plan, progress)| {
+            let plan = plan.as_ref().filter(|_| !progress.is_complete())?;
+            let remaining = progress.remaining() as u64;
+            let start = plan.samples_to_skip;
+            Some((plan.index, start..start.saturating_add(remaining)))
+        })
+        .collect();
+    let piece_block = |start: u64, length: u64, channels: Vec<RawDataMeta>| {
+        Ok::<_, TdmsError>(DataBlock {
+            start,
+            length: NonZeroU64::new(length).ok_or(TdmsError::ZeroLengthDataBlock)?,
+            layout: block.layout,
+            channels,
+            byte_order: block.byte_order,
+        })
+    };
+    let with_values = |meta: &RawDataMeta, number_of_values: u64| RawDataMeta {
+        number_of_values,
+        ..meta.clone()
+    };
+
+    let mut pieces = Vec::new();
+    let mut add_piece = |block: DataBlock, channel_indexes: Vec<Option<ChannelReadPlan>>| {
+        if channel_indexes.iter().any(Option::is_some) {
+            pieces.push(BlockPiece {
+                block,
+                location: BlockRead {
+                    data_block: location.data_block,
+                    channel_indexes,
+                },
+            });
+        }
+    };
+
+    match block.layout {
+        DataLayout::Interleaved => {
+            let rows = block.channels[0].number_of_values;
+            if block.channels.iter().any(|c| c.number_of_values != rows) {
+                return Ok(whole());
+            }
+            // Rows carry on from one chunk to the next so we can ignore the chunks.
+            let row_size = chunk_size / rows;
+            let total_rows = rows * chunks;
+            let rows_per_piece = (max_bytes / row_size).max(1);
+            let mut row = 0;
+            while row < total_rows {
+                let count = rows_per_piece.min(total_rows - row);
+                let channel_indexes = wanted
+                    .iter()
+                    .map(|wanted| piece_plan(wanted.as_ref()?, row..row + count, None))
+                    .collect();
+                let channels = block
+                    .channels
+                    .iter()
+                    .map(|meta| with_values(meta, count))
+                    .collect();
+                add_piece(
+                    piece_block(block.start + row * row_size, count * row_size, channels)?,
+                    channel_indexes,
+                );
+                row += count;
+            }
+        }
+        DataLayout::Contigious if chunk_size <= max_bytes => {
+            let chunks_per_piece = max_bytes / chunk_size;
+            let mut chunk = 0;
+            while chunk < chunks {
+                let count = chunks_per_piece.min(chunks - chunk);
+                let channel_indexes = wanted
+                    .iter()
+                    .map(|wanted| {
+                        let (index, _) = wanted.as_ref()?;
+                        let values = block.channels[*index].number_of_values;
+                        let samples = chunk * values..(chunk + count) * values;
+                        piece_plan(wanted.as_ref()?, samples, None)
+                    })
+                    .collect();
+                add_piece(
+                    piece_block(
+                        block.start + chunk * chunk_size,
+                        count * chunk_size,
+                        block.channels.clone(),
+                    )?,
+                    channel_indexes,
+                );
+                chunk += count;
+            }
+        }
+        DataLayout::Contigious => {
+            for chunk in 0..chunks {
+                for (output_index, channel) in wanted.iter().enumerate() {
+                    let Some(channel) = channel else {
+                        continue;
+                    };
+                    let index = channel.0;
+                    let meta = block.channels.get(index).ok_or_else(|| {
+                        TdmsError::DataBlockNotFound(
+                            ChannelPath::new("MIXED", "MIXED"),
+                            location.data_block,
+                        )
+                    })?;
+                    let value_size = meta.data_type.size() as u64;
+                    let offset: u64 = block.channels[..index]
+                        .iter()
+                        .map(|channel| channel.number_of_values * channel.data_type.size() as u64)
+                        .sum();
+                    let channel_start = block.start + chunk * chunk_size + offset;
+                    let values_per_piece = (max_bytes / value_size).max(1);
+
+                    let mut value = 0;
+                    while value < meta.number_of_values {
+                        let count = values_per_piece.min(meta.number_of_values - value);
+                        let first = chunk * meta.number_of_values + value;
+                        let mut channel_indexes = vec![None; wanted.len()];
+                        // The piece only holds this channel.
+                        channel_indexes[output_index] =
+                            piece_plan(channel, first..first + count, Some(0));
+                        add_piece(
+                            piece_block(
+                                channel_start + value * value_size,
+                                count * value_size,
+                                vec![with_values(meta, count)],
+                            )?,
+                            channel_indexes,
+                        );
+                        value += count;
+                    }
+                }
+            }
+        }
+    }
+    Ok(pieces)
+}
+
+/// Plan the read of a piece holding the `samples` range of a channel.
+///
+/// Returns `None` if none of the samples are wanted. The channel index in the piece
+/// is the same as the block unless `index` is given.
+fn piece_plan(
+    wanted: &(usize, Range<u64>),
+    samples: Range<u64>,
+    index: Option<usize>,
+) -> Option<ChannelReadPlan> {
+    let (block_index, wanted) = wanted;
+    if samples.start >= wanted.end || samples.end <= wanted.start {
+        return None;
+    }
+    Some(ChannelReadPlan {
+        index: index.unwrap_or(*block_index),
+        samples_to_skip: wanted.start.saturating_sub(samples.start),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::raw_data::Endianess;
+
+    fn f64_block(layout: DataLayout, values: &[u64], chunks: u64) -> DataBlock {
+        let channels: Vec<RawDataMeta> = values
+            .iter()
+            .map(|&number_of_values| RawDataMeta {
+                data_type: crate::io::data_types::DataType::DoubleFloat,
+                number_of_values,
+                total_size_bytes: None,
+            })
+            .collect();
+        let chunk_size: u64 = values.iter().sum::<u64>() * 8;
+        DataBlock {
+            start: 1000,
+            length: NonZeroU64::new(chunk_size * chunks).unwrap(),
+            layout,
+            channels,
+            byte_order: Endianess::Little,
+        }
+    }
+
+    fn block_read(channels: &[Option<(usize, u64)>]) -> BlockRead {
+        BlockRead {
+            data_block: 3,
+            channel_indexes: channels
+                .iter()
+                .map(|channel| {
+                    channel.map(|(index, samples_to_skip)| ChannelReadPlan {
+                        index,
+                        samples_to_skip,
+                    })
+                })
+                .collect(),
+        }
+    }
+
+    fn piece_ranges(pieces: &[BlockPiece]) -> Vec<(u64, u64)> {
+        pieces
+            .iter()
+            .map(|piece| (piece.block.start, piece.block.length.get()))
+            .collect()
+    }
+
+    #[test]
+    fn test_split_contiguous_groups_chunks() {
+        // 4 chunks of 2 channels with 10 values each.
+        let block = f64_block(DataLayout::Contigious, &[10, 10], 4);
+        let location = block_read(&[Some((1, 25))]);
+        let progress = [ChannelProgress::new(100)];
+        let pieces = split_block(&block, &location, &progress, 320).unwrap();
+
+        // The first 2 chunks are skipped entirely.
+        assert_eq!(piece_ranges(&pieces), vec![(1320, 320)]);
+        assert_eq!(pieces[0].block.channels, block.channels);
+        assert_eq!(pieces[0].location, block_read(&[Some((1, 5))]));
+    }
+
+    #[test]
+    fn test_split_contiguous_large_chunk_reads_channel_only() {
+        // 1 chunk of 2 channels with 100 values each.
+        let block = f64_block(DataLayout::Contigious, &[100, 100], 1);
+        let location = block_read(&[None, Some((1, 30))]);
+        let progress = [ChannelProgress::new(0), ChannelProgress::new(40)];
+        let pieces = split_block(&block, &location, &progress, 160).unwrap();
+
+        // Only the pieces of channel 2 holding samples 30 to 70.
+        assert_eq!(
+            piece_ranges(&pieces),
+            vec![(1960, 160), (2120, 160), (2280, 160)]
+        );
+        for piece in &pieces {
+            assert_eq!(piece.block.channels.len(), 1);
+            assert_eq!(piece.block.channels[0].number_of_values, 20);
+        }
+        assert_eq!(pieces[0].location, block_read(&[None, Some((0, 10))]));
+        assert_eq!(pieces[1].location, block_read(&[None, Some((0, 0))]));
+    }
+
+    #[test]
+    fn test_split_interleaved_into_rows() {
+        // 2 chunks of 2 channels with 10 values each.
+        let block = f64_block(DataLayout::Interleaved, &[10, 10], 2);
+        let location = block_read(&[Some((0, 12)), Some((1, 10))]);
+        // The second channel only needs 10 more values after earlier blocks.
+        let progress = [ChannelProgress::new(5), ChannelProgress::new(10)];
+        let pieces = split_block(&block, &location, &progress, 80).unwrap();
+
+        // Pieces of 5 rows, skipping the first 2.
+        assert_eq!(piece_ranges(&pieces), vec![(1160, 80), (1240, 80)]);
+        assert_eq!(pieces[0].block.channels[0].number_of_values, 5);
+        assert_eq!(
+            pieces[0].location,
+            block_read(&[Some((0, 2)), Some((1, 0))])
+        );
+        assert_eq!(
+            pieces[1].location,
+            block_read(&[Some((0, 0)), Some((1, 0))])
+        );
+    }
+
+    #[test]
+    fn test_split_keeps_string_blocks_whole() {
+        let mut block = f64_block(DataLayout::Contigious, &[10], 1);
+        block.channels.push(RawDataMeta {
+            data_type: crate::io::data_types::DataType::TdmsString,
+            number_of_values: 10,
+            total_size_bytes: Some(100),
+        });
+        let location = block_read(&[Some((0, 0))]);
+        let pieces = split_block(&block, &location, &[ChannelProgress::new(10)], 16).unwrap();
+        assert_eq!(pieces.len(), 1);
+        assert_eq!(pieces[0].block, block);
+    }
+}
@@ -1,43 +1,58 @@
+use std::io::{Cursor, Read, Seek, SeekFrom};
+
+use super::block_cache::BlockCache;
+use crate::meta_data::RawDataMeta;
 use crate::paths::ChannelPath;
-use crate::raw_data::BlockReadChannelConfig;
-use crate::{TdmsFile, error::TdmsError, index::DataLocation, io::data_types::TdmsStorageType};
+use crate::raw_data::{BlockReadChannelConfig, ChunkSize, DataBlock, DataLayout, Endianess};
+use crate::{
+    TdmsFile,
+    error::TdmsError,
+    index::{DataLocation, Index},
+    io::data_types::TdmsStorageType,
+};
 
 #[derive(Eq, PartialEq, Clone, Debug)]
-struct ChannelReadPlan {
-    index: usize,
-    samples_to_skip: u64,
+pub(super) struct ChannelReadPlan {
+    pub(super) index: usize,
+    pub(super) samples_to_skip: u64,
 }
 
 #[derive(Eq, PartialEq, Clone, Debug)]
-struct BlockRead {
+pub(super) struct BlockRead {
     ///The data block index/number.
-    data_block: usize,
+    pub(super) data_block: usize,
     ///The channel locations in this block.
     /// `None` means the channel has no data in this block.
     ///
     /// todo: can we avoid a vec here? It should be small
     /// so smallvec or array may work.
-    channel_indexes: Vec<Option<ChannelReadPlan>>,
+    pub(super) channel_indexes: Vec<Option<ChannelReadPlan>>,
 }
 
 #[derive(Eq, PartialEq, Clone, Debug)]
-struct ChannelProgress {
+pub(super) struct ChannelProgress {
     samples_read: usize,
     samples_target: usize,
 }
 
 impl ChannelProgress {
-    fn new(samples_target: usize) -> Self {
+    pub(super) fn new(samples_target: usize) -> Self {
         Self {
             samples_read: 0,
             samples_target,
         }
     }
 
-    fn is_complete(&self) -> bool {
+    pub(super) fn is_complete(&self) -> bool {
         self.samples_read >= self.samples_target
     }
 
+    /// The number of samples still needed.
+    #[cfg(any(feature = "parallel", feature = "tokio"))]
+    pub(super) fn remaining(&self) -> usize {
+        self.samples_target.saturating_sub(self.samples_read)
+    }
+
     fn add_samples(&mut self, samples: usize) {
         self.samples_read += samples;
     }
@@ -82,13 +97,7 @@ impl<F: std::io::Read + std::io::Seek> TdmsFile<F> {
         start: u64,
         output: &mut [D],
     ) -> Result<(), TdmsError> {
-        let data_positions = self
-            .index
-            .get_channel_data_positions(channel)
-            .ok_or_else(|| TdmsError::MissingObject(channel.path().to_owned()))?;
-
-        let plan = read_plan(&[data_positions], &[start]);
-        self.execute_read_plan(plan, &mut [output])
+        self.read_channels_from(&[channel], start, &mut [output])
     }
 
     /// Read multiple channels from the tdms file.
@@ -123,81 +132,286 @@ impl<F: std::io::Read + std::io::Seek> TdmsFile<F> {
         start: u64,
         output: &mut [&mut [D]],
     ) -> Result<(), TdmsError> {
-        let channel_positions = channels
-            .iter()
-            .map(|channel| {
-                self.index
-                    .get_channel_data_positions(channel.as_ref())
-                    .ok_or_else(|| TdmsError::MissingObject(channel.as_ref().path().to_owned()))
-            })
-            .collect::<Result<Vec<&[DataLocation]>, TdmsError>>()?;
+        let plan = plan_channels_read(&self.index, channels, start)?;
+        match &mut self.cache {
+            Some(cache) => {
+                execute_cached_read_plan(&self.index, &mut self.file, cache, plan, output)
+            }
+            None => execute_read_plan(&self.index, &mut self.file, plan, output),
+        }
+    }
+}
+
+/// Plan the block reads for the channels starting at the same sample.
+pub(super) fn plan_channels_read(
+    index: &Index,
+    channels: &[impl AsRef<ChannelPath>],
+    start: u64,
+) -> Result<Vec<BlockRead>, TdmsError> {
+    let channel_positions = channels
+        .iter()
+        .map(|channel| {
+            index
+                .get_channel_data_positions(channel.as_ref())
+                .ok_or_else(|| TdmsError::MissingObject(channel.as_ref().path().to_owned()))
+        })
+        .collect::<Result<Vec<&[DataLocation]>, TdmsError>>()?;
+
+    let start_skips: Vec<u64> = vec![start; channels.len()];
+    Ok(read_plan(&channel_positions[..], &start_skips))
+}
 
-        let start_skips: Vec<u64> = vec![start; channels.len()];
-        let plan = read_plan(&channel_positions[..], &start_skips);
+/// Execute a read plan, reading data from blocks into the output slices.
+///
+/// This is the core read execution logic used by all read methods.
+/// The plan specifies which blocks to read and any per-channel skip amounts.
+///
+/// Neighbouring small blocks are loaded in a single read using [`plan_read_spans`]
+/// and then decoded from memory.
+pub(super) fn execute_read_plan<D: TdmsStorageType>(
+    index: &Index,
+    file: &mut (impl Read + Seek),
+    plan: Vec<BlockRead>,
+    output: &mut [&mut [D]],
+) -> Result<(), TdmsError> {
+    let mut channel_progress: Vec<ChannelProgress> = output
+        .iter()
+        .map(|out_slice| ChannelProgress::new(out_slice.len()))
+        .collect();
 
-        self.execute_read_plan(plan, output)
+    let blocks = plan
+        .iter()
+        .map(|location| get_block(index, location.data_block))
+        .collect::<Result<Vec<_>, TdmsError>>()?;
+    let spans = plan_read_spans(
+        blocks.iter().map(|block| (block.start, block.length.get())),
+        MAX_SPAN_GAP,
+        MAX_SPAN_BYTES,
+    );
+
+    let mut locations = plan.iter().zip(blocks.iter());
+
+    for span in spans {
+        let span_locations = locations.by_ref().take(span.blocks);
+
+        if span.blocks == 1 {
+            for (location, block) in span_locations {
+                read_block(block, file, location, output, &mut channel_progress)?;
+            }
+        } else {
+            let mut buffer = BlockBuffer::load(file, span.start, span.end - span.start)?;
+            for (location, block) in span_locations {
+                read_block(block, &mut buffer, location, output, &mut channel_progress)?;
+                if all_channels_complete(&channel_progress) {
+                    break;
+                }
+            }
+        }
+
+        if all_channels_complete(&channel_progress) {
+            break;
+        }
     }
 
-    /// Execute a read plan, reading data from blocks into the output slices.
-    ///
-    /// This is the core read execution logic used by all read methods.
-    /// The plan specifies which blocks to read and any per-channel skip amounts.
-    fn execute_read_plan<D: TdmsStorageType>(
-        &mut self,
-        plan: Vec<BlockRead>,
-        output: &mut [&mut [D]],
-    ) -> Result<(), TdmsError> {
-        let mut channel_progress: Vec<ChannelProgress> = output
-            .iter()
-            .map(|out_slice| ChannelProgress::new(out_slice.len()))
-            .collect();
+    Ok(())
+}
 
-        for location in plan {
-            // Check if any channel needs to skip at the start of this block
-            let any_skip_needed = location
-                .channel_indexes
-                .iter()
-                .any(|plan| plan.is_some() && plan.as_ref().unwrap().samples_to_skip > 0);
-
-            let block = self
-                .index
-                .get_data_block(location.data_block)
-                .ok_or_else(|| {
-                    TdmsError::DataBlockNotFound(
-                        ChannelPath::new("MIXED", "MIXED"),
-                        location.data_block,
-                    )
-                })?;
+/// Execute a read plan using the block cache.
+///
+/// Each channel's bytes are read for the whole block on a miss so later reads of any
+/// part of that block can be decoded from the cache.
+pub(super) fn execute_cached_read_plan<D: TdmsStorageType>(
+    index: &Index,
+    file: &mut (impl Read + Seek),
+    cache: &mut BlockCache,
+    plan: Vec<BlockRead>,
+    output: &mut [&mut [D]],
+) -> Result<(), TdmsError> {
+    let mut channel_progress: Vec<ChannelProgress> = output
+        .iter()
+        .map(|out_slice| ChannelProgress::new(out_slice.len()))
+        .collect();
+
+    for location in plan.iter() {
+        let block = get_block(index, location.data_block)?;
+        let chunks = block.number_of_chunks()? as u64;
 
-            // Use fast path if no skip needed, slow path otherwise
-            let location_samples_read = if any_skip_needed {
-                let mut channels_with_skip =
-                    get_block_read_data_with_skip(&location, output, &channel_progress);
-                block.read_with_per_channel_skip(&mut self.file, &mut channels_with_skip)?
+        for ((plan, output), progress) in location
+            .channel_indexes
+            .iter()
+            .zip(output.iter_mut())
+            .zip(channel_progress.iter_mut())
+        {
+            let Some(plan) = plan else {
+                continue;
+            };
+            if progress.is_complete() {
+                continue;
+            }
+            let output = &mut output[progress.samples_read..];
+            let key = (location.data_block, plan.index);
+            let meta = block.channels.get(plan.index).ok_or_else(|| {
+                TdmsError::DataBlockNotFound(
+                    ChannelPath::new("MIXED", "MIXED"),
+                    location.data_block,
+                )
+            })?;
+
+            let samples_read = if let Some(bytes) = cache.get(key) {
+                decode_cached(block, meta, bytes, plan.samples_to_skip, output)?
             } else {
-                let mut channels_to_read =
-                    get_block_read_data(&location, output, &channel_progress);
-                block.read(&mut self.file, &mut channels_to_read)?
+                let block_bytes = meta.number_of_values * chunks * meta.data_type.size() as u64;
+                let bytes = if block_bytes > cache.max_bytes() as u64 {
+                    None
+                } else {
+                    read_channel_bytes(block, plan.index, file)?
+                };
+
+                match bytes {
+                    Some(bytes) => {
+                        let samples_read =
+                            decode_cached(block, meta, &bytes, plan.samples_to_skip, output)?;
+                        cache.insert(key, bytes);
+                        samples_read
+                    }
+                    // Too big to cache or not a fixed size so just read what we need.
+                    None => {
+                        block.read_single_from(plan.index, plan.samples_to_skip, file, output)?
+                    }
+                }
             };
+            progress.add_samples(samples_read);
+        }
 
-            // Update progress
-            for (plan, progress) in location
-                .channel_indexes
+        if all_channels_complete(&channel_progress) {
+            break;
+        }
+    }
+
+    Ok(())
+}
+
+/// Read the bytes of a single channel for the whole block.
+///
+/// Returns `None` if the block doesn't have a fixed chunk size, such as when it holds strings.
+fn read_channel_bytes(
+    block: &DataBlock,
+    channel_index: usize,
+    file: &mut (impl Read + Seek),
+) -> Result<Option<Vec<u8>>, TdmsError> {
+    let ChunkSize::Fixed(chunk_size) = block.chunk_size()? else {
+        return Ok(None);
+    };
+    let meta = &block.channels[channel_index];
+    let value_size = meta.data_type.size() as u64;
+    if value_size == 0 {
+        return Ok(None);
+    }
+    let chunks = block.number_of_chunks()? as u64;
+    let channel_bytes = (meta.number_of_values * value_size) as usize;
+    let mut bytes = Vec::with_capacity(channel_bytes * chunks as usize);
+
+    match block.layout {
+        DataLayout::Contigious => {
+            let offset: u64 = block.channels[..channel_index]
                 .iter()
-                .zip(channel_progress.iter_mut())
-            {
-                if plan.is_some() {
-                    progress.add_samples(location_samples_read);
-                }
+                .map(|channel| channel.number_of_values * channel.data_type.size() as u64)
+                .sum();
+            for chunk in 0..chunks {
+                file.seek(SeekFrom::Start(block.start + chunk * chunk_size + offset))?;
+                let filled = bytes.len();
+                bytes.resize(filled + channel_bytes, 0);
+                file.read_exact(&mut bytes[filled..])?;
             }
-
-            if all_channels_complete(&channel_progress) {
-                break;
+        }
+        DataLayout::Interleaved => {
+            let row_size: usize = block
+                .channels
+                .iter()
+                .map(|channel| channel.data_type.size() as usize)
+                .sum();
+            let offset: usize = block.channels[..channel_index]
+                .iter()
+                .map(|channel| channel.data_type.size() as usize)
+                .sum();
+            let value_size = value_size as usize;
+            let mut chunk = vec![0u8; chunk_size as usize];
+            file.seek(SeekFrom::Start(block.start))?;
+            for _ in 0..chunks {
+                file.read_exact(&mut chunk)?;
+                for row in chunk.chunks_exact(row_size) {
+                    bytes.extend_from_slice(&row[offset..offset + value_size]);
+                }
             }
         }
+    }
+    Ok(Some(bytes))
+}
 
-        Ok(())
+/// Decode the cached bytes for a block into the output, returning the number of samples decoded.
+fn decode_cached<D: TdmsStorageType>(
+    block: &DataBlock,
+    meta: &RawDataMeta,
+    bytes: &[u8],
+    samples_to_skip: u64,
+    output: &mut [D],
+) -> Result<usize, TdmsError> {
+    if !D::supports_data_type(&meta.data_type) {
+        return Err(TdmsError::DataTypeMismatch(meta.data_type, D::NATURAL_TYPE));
+    }
+    let value_size = meta.data_type.size() as usize;
+    let skip = (samples_to_skip as usize).saturating_mul(value_size);
+    let available = bytes.get(skip..).unwrap_or_default();
+    let count = (available.len() / value_size).min(output.len());
+    let mut reader = &available[..count * value_size];
+    match block.byte_order {
+        Endianess::Little => D::read_le_slice(&mut reader, &mut output[..count])?,
+        Endianess::Big => D::read_be_slice(&mut reader, &mut output[..count])?,
     }
+    Ok(count)
+}
+
+pub(super) fn get_block(index: &Index, data_block: usize) -> Result<&DataBlock, TdmsError> {
+    index
+        .get_data_block(data_block)
+        .ok_or_else(|| TdmsError::DataBlockNotFound(ChannelPath::new("MIXED", "MIXED"), data_block))
+}
+
+/// Read the planned channels from a single block and update the progress.
+pub(super) fn read_block<D: TdmsStorageType>(
+    block: &DataBlock,
+    reader: &mut (impl Read + Seek),
+    location: &BlockRead,
+    output: &mut [&mut [D]],
+    channel_progress: &mut [ChannelProgress],
+) -> Result<(), TdmsError> {
+    // Check if any channel needs to skip at the start of this block
+    let any_skip_needed = location
+        .channel_indexes
+        .iter()
+        .any(|plan| plan.is_some() && plan.as_ref().unwrap().samples_to_skip > 0);
+
+    // Use fast path if no skip needed, slow path otherwise
+    let location_samples_read = if any_skip_needed {
+        let mut channels_with_skip =
+            get_block_read_data_with_skip(location, output, channel_progress);
+        block.read_with_per_channel_skip(reader, &mut channels_with_skip)?
+    } else {
+        let mut channels_to_read = get_block_read_data(location, output, channel_progress);
+        block.read(reader, &mut channels_to_read)?
+    };
+
+    // Update progress
+    for (plan, progress) in location
+        .channel_indexes
+        .iter()
+        .zip(channel_progress.iter_mut())
+    {
+        if plan.is_some() {
+            progress.add_samples(location_samples_read);
+        }
+    }
+    Ok(())
 }
 
 /// Get the read parameters and output for this particular block.
@@ -250,7 +464,7 @@ fn get_block_read_data_with_skip<'a, 'b: 'o, 'c: 'o, 'o, D: TdmsStorageType>(
         .collect::<Vec<_>>()
 }
 
-fn all_channels_complete(channel_progress: &[ChannelProgress]) -> bool {
+pub(super) fn all_channels_complete(channel_progress: &[ChannelProgress]) -> bool {
     channel_progress
         .iter()
         .all(|progress| progress.is_complete())
@@ -327,6 +541,115 @@ fn read_plan(channel_positions: &[&[DataLocation]], start_skips: &[u64]) -> Vec<
     }
 }
 
+/// The largest gap between blocks that we will read through rather than seek over.
+pub(super) const MAX_SPAN_GAP: u64 = 4 * 1024;
+
+/// The largest span we will load in a single read.
+///
+/// Blocks bigger than this are read directly from the file.
+pub(super) const MAX_SPAN_BYTES: u64 = 1024 * 1024;
+
+/// A range of the file covering one or more consecutive blocks in the read plan.
+#[derive(Eq, PartialEq, Clone, Debug)]
+pub(super) struct ReadSpan {
+    pub(super) start: u64,
+    pub(super) end: u64,
+    /// The number of blocks from the plan in this span.
+    pub(super) blocks: usize,
+}
+
+/// Plan the file reads for the blocks in the read plan.
+///
+/// This is the file level plan. Neighbouring blocks are merged into a single span
+/// when the gap between them is at most `max_gap` bytes and the span stays within `max_span` bytes.
+/// This avoids a seek and read per block when a file has many small segments.
+///
+/// `blocks` is the start and length of each block in the plan, in file order.
+pub(super) fn plan_read_spans(
+    blocks: impl Iterator<Item = (u64, u64)>,
+    max_gap: u64,
+    max_span: u64,
+) -> Vec<ReadSpan> {
+    let mut spans: Vec<ReadSpan> = Vec::new();
+
+    for (start, length) in blocks {
+        let end = start + length;
+        if let Some(span) = spans.last_mut() {
+            let gap = start.checked_sub(span.end);
+            if gap.is_some_and(|gap| gap <= max_gap) && end - span.start <= max_span {
+                span.end = end;
+                span.blocks += 1;
+                continue;
+            }
+        }
+        spans.push(ReadSpan {
+            start,
+            end,
+            blocks: 1,
+        });
+    }
+
+    spans
+}
+
+/// Holds bytes loaded from the file but can be seeked using file positions.
+///
+/// This lets us decode blocks from memory with the same readers as the file.
+pub(super) struct BlockBuffer {
+    start: u64,
+    cursor: Cursor<Vec<u8>>,
+}
+
+impl BlockBuffer {
+    /// Load `length` bytes from the file at `start`.
+    ///
+    /// This allows a short read in case the final segment is incomplete.
+    pub(super) fn load(
+        file: &mut (impl Read + Seek),
+        start: u64,
+        length: u64,
+    ) -> Result<Self, TdmsError> {
+        file.seek(SeekFrom::Start(start))?;
+        let mut bytes = Vec::new();
+        bytes
+            .try_reserve_exact(length as usize)
+            .map_err(|_| TdmsError::VecAllocationFailed)?;
+        file.take(length).read_to_end(&mut bytes)?;
+        Ok(Self::new(start, bytes))
+    }
+
+    /// Wrap bytes which were loaded from the file at `start`.
+    pub(super) fn new(start: u64, bytes: Vec<u8>) -> Self {
+        Self {
+            start,
+            cursor: Cursor::new(bytes),
+        }
+    }
+}
+
+impl Read for BlockBuffer {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        self.cursor.read(buf)
+    }
+}
+
+impl Seek for BlockBuffer {
+    fn seek(&mut self, pos: SeekFrom) -> std::io::Result<u64> {
+        let pos = match pos {
+            SeekFrom::Start(position) => {
+                SeekFrom::Start(position.checked_sub(self.start).ok_or_else(|| {
+                    std::io::Error::new(
+                        std::io::ErrorKind::InvalidInput,
+                        "seek before the start of the loaded data",
+                    )
+                })?)
+            }
+            other => other,
+        };
+        Ok(self.cursor.seek(pos)? + self.start)
+    }
+}
+
 #[cfg(test)]
 mod tests {
 
@@ -685,6 +1008,64 @@ mod tests {
         assert_eq!(plan, expected_plan);
     }
 
+    #[test]
+    fn test_read_spans_merge_close_blocks() {
+        let blocks = [(100, 50), (170, 50), (1000, 50), (1050, 50)];
+        let spans = plan_read_spans(blocks.into_iter(), 20, 10_000);
+        assert_eq!(
+            spans,
+            vec![
+                ReadSpan {
+                    start: 100,
+                    end: 220,
+                    blocks: 2,
+                },
+                ReadSpan {
+                    start: 1000,
+                    end: 1100,
+                    blocks: 2,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_read_spans_limit_size() {
+        let blocks = [(0, 40), (40, 40), (80, 40), (120, 200)];
+        let spans = plan_read_spans(blocks.into_iter(), 20, 100);
+        assert_eq!(
+            spans,
+            vec![
+                ReadSpan {
+                    start: 0,
+                    end: 80,
+                    blocks: 2,
+                },
+                ReadSpan {
+                    start: 80,
+                    end: 120,
+                    blocks: 1,
+                },
+                ReadSpan {
+                    start: 120,
+                    end: 320,
+                    blocks: 1,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_block_buffer_uses_file_positions() {
+        let mut file = Cursor::new((0u8..200).collect::<Vec<u8>>());
+        let mut buffer = BlockBuffer::load(&mut file, 100, 4).unwrap();
+        assert_eq!(buffer.seek(SeekFrom::Start(102)).unwrap(), 102);
+        let mut byte = [0u8];
+        buffer.read_exact(&mut byte).unwrap();
+        assert_eq!(byte, [102]);
+        assert!(buffer.seek(SeekFrom::Start(99)).is_err());
+    }
+
     #[test]
     fn test_progress_complete() {
         let mut progress = ChannelProgress::new(10);
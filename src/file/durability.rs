@@ -0,0 +1,190 @@
+//! Support for making written data durable on disk.
+//!
+//! [`crate::TdmsFileWriter::sync`] only flushes our buffers to the OS. To survive a power
+//! loss we also need the OS to write its cache to the storage device.
+
+use std::fs::File;
+use std::time::{Duration, Instant};
+
+use crate::error::TdmsError;
+use crate::index::Index;
+
+/// A sink which can force written data out to its storage device.
+///
+/// This is implemented for [`File`] using [`File::sync_data`] and [`File::sync_all`].
+pub trait SyncToDisk {
+    /// Write the data to the storage device. Metadata such as modification times may not be updated.
+    fn sync_data(&self) -> std::io::Result<()>;
+    /// Write the data and all metadata to the storage device.
+    fn sync_all(&self) -> std::io::Result<()>;
+}
+
+impl SyncToDisk for File {
+    fn sync_data(&self) -> std::io::Result<()> {
+        File::sync_data(self)
+    }
+
+    fn sync_all(&self) -> std::io::Result<()> {
+        File::sync_all(self)
+    }
+}
+
+/// Controls when a writer automatically syncs data to disk.
+///
+/// By default nothing is synced automatically. When both limits are set the
+/// writer syncs when either is reached. The interval is only checked when a
+/// segment is written so it is a minimum rather than a timer.
+///
+/// # Example
+///
+/// ```rust
+/// use std::time::Duration;
+/// use tedium::SyncPolicy;
+///
+/// let policy = SyncPolicy::default()
+///     .every_segments(100)
+///     .every_interval(Duration::from_secs(1));
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SyncPolicy {
+    segments: Option<usize>,
+    interval: Option<Duration>,
+}
+
+impl SyncPolicy {
+    /// Sync once this many segments have been written since the last sync.
+    ///
+    /// Zero disables the segment limit.
+    pub fn every_segments(mut self, segments: usize) -> Self {
+        self.segments = (segments > 0).then_some(segments);
+        self
+    }
+
+    /// Sync on the first segment written after this much time since the last sync.
+    pub fn every_interval(mut self, interval: Duration) -> Self {
+        self.interval = Some(interval);
+        self
+    }
+
+    fn is_due(&self, segments: usize, since_sync: Duration) -> bool {
+        let segments_due = self.segments.is_some_and(|limit| segments >= limit);
+        let interval_due = self.interval.is_some_and(|limit| since_sync >= limit);
+        segments_due || interval_due
+    }
+}
+
+/// Flushes the writer and syncs the data to disk.
+type SyncFn<W> = fn(&mut W) -> Result<(), TdmsError>;
+
+/// Tracks the sync state for a writer.
+#[derive(Debug)]
+pub(super) struct SyncState<W> {
+    policy: SyncPolicy,
+    segments_since_sync: usize,
+    last_sync: Instant,
+    synced_length: Option<u64>,
+    /// This is captured when the policy is set so that writers over
+    /// streams which can't be synced don't need the [`SyncToDisk`] bound.
+    sync: Option<SyncFn<W>>,
+}
+
+impl<W> Default for SyncState<W> {
+    fn default() -> Self {
+        Self {
+            policy: SyncPolicy::default(),
+            segments_since_sync: 0,
+            last_sync: Instant::now(),
+            synced_length: None,
+            sync: None,
+        }
+    }
+}
+
+impl<W> SyncState<W> {
+    pub fn set_policy(&mut self, policy: SyncPolicy, sync: SyncFn<W>) {
+        self.policy = policy;
+        self.sync = Some(sync);
+    }
+
+    /// The length of the file which was durable at the last sync.
+    pub fn synced_length(&self) -> Option<u64> {
+        self.synced_length
+    }
+
+    /// Record a sync has completed covering everything in the index.
+    pub fn synced(&mut self, index: &Index) {
+        self.segments_since_sync = 0;
+        self.last_sync = Instant::now();
+        self.synced_length = Some(index.indexed_length());
+    }
+
+    /// Record a segment has been written and sync if the policy requires it.
+    pub fn segment_written(&mut self, writer: &mut W, index: &Index) -> Result<(), TdmsError> {
+        self.segments_since_sync += 1;
+        let Some(sync) = self.sync else {
+            return Ok(());
+        };
+        if self
+            .policy
+            .is_due(self.segments_since_sync, self.last_sync.elapsed())
+        {
+            sync(writer)?;
+            self.synced(index);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn default_policy_never_due() {
+        let policy = SyncPolicy::default();
+        assert!(!policy.is_due(1_000_000, Duration::from_secs(1_000_000)));
+    }
+
+    #[test]
+    fn segments_policy_due_at_limit() {
+        let policy = SyncPolicy::default().every_segments(3);
+        assert!(!policy.is_due(2, Duration::ZERO));
+        assert!(policy.is_due(3, Duration::ZERO));
+    }
+
+    #[test]
+    fn zero_segments_disables_limit() {
+        let policy = SyncPolicy::default().every_segments(3).every_segments(0);
+        assert_eq!(policy, SyncPolicy::default());
+    }
+
+    #[test]
+    fn either_limit_triggers() {
+        let policy = SyncPolicy::default()
+            .every_segments(10)
+            .every_interval(Duration::from_millis(100));
+        assert!(policy.is_due(1, Duration::from_millis(100)));
+        assert!(policy.is_due(10, Duration::ZERO));
+        assert!(!policy.is_due(9, Duration::from_millis(99)));
+    }
+
+    #[test]
+    fn segment_written_calls_sync_when_due() {
+        let mut state = SyncState::<usize>::default();
+        state.set_policy(SyncPolicy::default().every_segments(2), |count| {
+            *count += 1;
+            Ok(())
+        });
+        let index = Index::new();
+        let mut sync_count = 0;
+
+        state.segment_written(&mut sync_count, &index).unwrap();
+        assert_eq!(sync_count, 0);
+        assert_eq!(state.synced_length(), None);
+        state.segment_written(&mut sync_count, &index).unwrap();
+        assert_eq!(sync_count, 1);
+        assert_eq!(state.synced_length(), Some(0));
+        state.segment_written(&mut sync_count, &index).unwrap();
+        assert_eq!(sync_count, 1);
+    }
+}
@@ -1,17 +1,19 @@
+use super::durability::{SyncPolicy, SyncState, SyncToDisk};
 use crate::error::TdmsError;
 use crate::index::{DataFormat, Index};
 use crate::io::data_types::TdmsStorageType;
-use crate::io::writer::TdmsWriter;
-use crate::meta_data::{MetaData, ObjectMetaData, Segment, ToC};
+use crate::io::writer::{BigEndianWriter, LittleEndianWriter, TdmsWriter};
+use crate::meta_data::{MetaData, ObjectMetaData, RawDataIndex, Segment, ToC};
 use crate::paths::ChannelPath;
 use crate::raw_data::{MultiChannelSlice, WriteBlock};
-use crate::{DataLayout, PropertyPath, PropertyValue};
-use std::io::Write;
+use crate::{DataLayout, PropertyPath, PropertyValue, TdmsFile};
+use std::io::{Read, Seek, Write};
 use std::num::NonZeroUsize;
 
 pub struct TdmsFileWriter<'a, F: Write + 'a, W: TdmsWriter<&'a mut F>> {
     index: &'a mut Index,
     writer: W,
+    sync_state: SyncState<W>,
     _file: std::marker::PhantomData<F>,
 }
 
@@ -25,6 +27,7 @@ impl<'a, F: Write, W: TdmsWriter<&'a mut F>> TdmsFileWriter<'a, F, W> {
         Self {
             index,
             writer,
+            sync_state: SyncState::default(),
             _file: std::marker::PhantomData,
         }
     }
@@ -42,9 +45,9 @@ impl<'a, F: Write, W: TdmsWriter<&'a mut F>> TdmsFileWriter<'a, F, W> {
         values: &'b [D],
         layout: DataLayout,
     ) -> Result<(), TdmsError> {
-        let stream = DataStreamWriter::new(self.index, &mut self.writer, channels, values, layout)?;
-        stream.end_stream()?;
-        Ok(())
+        write_channels(self.index, &mut self.writer, channels, values, layout)?;
+        self.sync_state
+            .segment_written(&mut self.writer, self.index)
     }
 
     /// Write the properties to the given path.
@@ -70,33 +73,286 @@ impl<'a, F: Write, W: TdmsWriter<&'a mut F>> TdmsFileWriter<'a, F, W> {
         path: &PropertyPath,
         properties: &[(&str, PropertyValue)],
     ) -> Result<(), TdmsError> {
-        let path = path.path();
-        let properties = properties
-            .iter()
-            .map(|(name, value)| (name.to_string(), (*value).clone()))
-            .collect();
+        write_properties(self.index, &mut self.writer, path, properties)?;
+        self.sync_state
+            .segment_written(&mut self.writer, self.index)
+    }
 
-        let object = ObjectMetaData {
-            path: path.to_string(),
-            properties,
-            raw_data_index: crate::meta_data::RawDataIndex::None,
-        };
+    /// Flushes any buffered data to the file by calling the sync method on the writer.
+    ///
+    /// This doesn't guarantee the data has reached the disk. Use [`Self::sync_data`] for that.
+    pub fn sync(&mut self) -> Result<(), TdmsError> {
+        self.writer.sync()
+    }
+}
 
-        let meta = MetaData {
-            objects: vec![object],
-        };
+impl<'a, F: Write + SyncToDisk, W: TdmsWriter<&'a mut F>> TdmsFileWriter<'a, F, W> {
+    /// Flush any buffered data and wait for the file data to reach the disk.
+    ///
+    /// Once this returns all segments written so far will survive a power loss.
+    pub fn sync_data(&mut self) -> Result<(), TdmsError> {
+        self.writer.sync()?;
+        self.writer.get_ref().sync_data()?;
+        self.sync_state.synced(self.index);
+        Ok(())
+    }
 
-        let segment =
-            self.writer
-                .write_segment(ToC::default(), Some(meta), Option::<&[u8]>::None)?;
-        self.index.add_segment(segment)?;
+    /// Flush any buffered data and wait for the file data and metadata to reach the disk.
+    pub fn sync_all(&mut self) -> Result<(), TdmsError> {
+        self.writer.sync()?;
+        self.writer.get_ref().sync_all()?;
+        self.sync_state.synced(self.index);
         Ok(())
     }
 
-    /// Forces the file to sync to disk by calling the sync method on the writer.
+    /// Set the policy for automatically calling [`Self::sync_data`] as segments are written.
+    ///
+    /// The policy only applies to this writer so keep the writer for the duration of logging.
+    pub fn set_sync_policy(&mut self, policy: SyncPolicy) {
+        self.sync_state.set_policy(policy, |writer: &mut W| {
+            writer.sync()?;
+            writer.get_ref().sync_data()?;
+            Ok(())
+        });
+    }
+
+    /// The length of the file that was on disk at the last sync by this writer.
+    ///
+    /// After a power loss, data up to this length is committed. Returns `None`
+    /// if this writer hasn't synced yet.
+    pub fn synced_length(&self) -> Option<u64> {
+        self.sync_state.synced_length()
+    }
+}
+
+/// A TDMS writer which owns the file and index.
+///
+/// Unlike [`TdmsFileWriter`] this doesn't borrow from a [`TdmsFile`] so it can be
+/// moved to another thread, for example a dedicated logging thread.
+///
+/// Create it with [`TdmsFile::into_writer`] and convert it back to a readable file
+/// with [`Self::into_file`].
+///
+/// # Example
+///
+/// ```rust
+/// use tedium::{TdmsFile, ChannelPath, DataLayout};
+///
+/// let fake_file = std::io::Cursor::new(vec![]);
+/// let file = TdmsFile::new(fake_file).unwrap();
+/// let mut writer = file.into_writer().unwrap();
+///
+/// let writer = std::thread::spawn(move || {
+///     writer.write_channels(
+///         &[ChannelPath::new("group", "channel")],
+///         &[1.0, 2.0, 3.0],
+///         DataLayout::Contigious,
+///     ).unwrap();
+///     writer
+/// }).join().unwrap();
+///
+/// let mut file = writer.into_file().unwrap();
+/// file.read_channel(&ChannelPath::new("group", "channel"), &mut [0.0f64; 3]).unwrap();
+/// ```
+pub struct TdmsWriterHandle<F: Write, W: TdmsWriter<F> = LittleEndianWriter<F>> {
+    index: Index,
+    writer: W,
+    sync_state: SyncState<W>,
+    _file: std::marker::PhantomData<F>,
+}
+
+impl<F: Write, W: TdmsWriter<F>> TdmsWriterHandle<F, W> {
+    /// Create a new writer handle from an existing index and writer.
+    ///
+    /// Normally this is created by calling [`crate::TdmsFile::into_writer`].
+    ///
+    /// The writer must be positioned at the end of the file described by the index.
+    pub(crate) fn new(index: Index, writer: W) -> Self {
+        Self {
+            index,
+            writer,
+            sync_state: SyncState::default(),
+            _file: std::marker::PhantomData,
+        }
+    }
+
+    /// Write the data to the given channels.
+    ///
+    /// See [`TdmsFileWriter::write_channels`] for details of the layout.
+    pub fn write_channels<D: TdmsStorageType, C: AsRef<ChannelPath>>(
+        &mut self,
+        channels: &[C],
+        values: &[D],
+        layout: DataLayout,
+    ) -> Result<(), TdmsError> {
+        write_channels(&mut self.index, &mut self.writer, channels, values, layout)?;
+        self.sync_state
+            .segment_written(&mut self.writer, &self.index)
+    }
+
+    /// Write the properties to the given path.
+    /// This will overwrite any existing properties.
+    pub fn write_properties(
+        &mut self,
+        path: &PropertyPath,
+        properties: &[(&str, PropertyValue)],
+    ) -> Result<(), TdmsError> {
+        write_properties(&mut self.index, &mut self.writer, path, properties)?;
+        self.sync_state
+            .segment_written(&mut self.writer, &self.index)
+    }
+
+    /// Flushes any buffered data to the file by calling the sync method on the writer.
+    ///
+    /// This doesn't guarantee the data has reached the disk. Use [`Self::sync_data`] for that.
     pub fn sync(&mut self) -> Result<(), TdmsError> {
         self.writer.sync()
     }
+
+    /// Finish writing and return the underlying writer.
+    ///
+    /// This flushes any buffered data first. Use this for streams
+    /// which need finishing such as compressors.
+    pub fn into_inner(self) -> Result<F, TdmsError> {
+        self.writer.into_inner()
+    }
+}
+
+impl<F: Write + SyncToDisk, W: TdmsWriter<F>> TdmsWriterHandle<F, W> {
+    /// Flush any buffered data and wait for the file data to reach the disk.
+    ///
+    /// See [`TdmsFileWriter::sync_data`].
+    pub fn sync_data(&mut self) -> Result<(), TdmsError> {
+        self.writer.sync()?;
+        self.writer.get_ref().sync_data()?;
+        self.sync_state.synced(&self.index);
+        Ok(())
+    }
+
+    /// Flush any buffered data and wait for the file data and metadata to reach the disk.
+    pub fn sync_all(&mut self) -> Result<(), TdmsError> {
+        self.writer.sync()?;
+        self.writer.get_ref().sync_all()?;
+        self.sync_state.synced(&self.index);
+        Ok(())
+    }
+
+    /// Set the policy for automatically calling [`Self::sync_data`] as segments are written.
+    pub fn set_sync_policy(&mut self, policy: SyncPolicy) {
+        self.sync_state.set_policy(policy, |writer: &mut W| {
+            writer.sync()?;
+            writer.get_ref().sync_data()?;
+            Ok(())
+        });
+    }
+
+    /// The length of the file that was on disk at the last sync by this writer.
+    ///
+    /// See [`TdmsFileWriter::synced_length`].
+    pub fn synced_length(&self) -> Option<u64> {
+        self.sync_state.synced_length()
+    }
+}
+
+impl<F: Write> TdmsWriterHandle<F> {
+    /// Create a writer for a new TDMS stream.
+    ///
+    /// This only requires [`Write`] so it can be used with sinks that can't be read
+    /// back or seeked such as sockets, pipes or compressors. The stream is assumed
+    /// to be empty so the index starts fresh.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use tedium::{TdmsWriterHandle, ChannelPath, DataLayout};
+    ///
+    /// let mut writer = TdmsWriterHandle::from_writer(Vec::new());
+    /// writer.write_channels(
+    ///     &[ChannelPath::new("group", "channel")],
+    ///     &[1.0, 2.0, 3.0],
+    ///     DataLayout::Contigious,
+    /// ).unwrap();
+    ///
+    /// let bytes: Vec<u8> = writer.into_inner().unwrap();
+    /// ```
+    pub fn from_writer(writer: F) -> Self {
+        Self::new(Index::new(), LittleEndianWriter::from_writer(writer))
+    }
+}
+
+impl<F: Write> TdmsWriterHandle<F, BigEndianWriter<F>> {
+    /// Create a writer for a new TDMS stream which writes big endian segments.
+    ///
+    /// See [`TdmsWriterHandle::from_writer`].
+    pub fn from_big_endian_writer(writer: F) -> Self {
+        Self::new(Index::new(), BigEndianWriter::from_writer(writer))
+    }
+}
+
+impl<F: Write + Read + Seek, W: TdmsWriter<F>> TdmsWriterHandle<F, W> {
+    /// Finish writing and return a [`TdmsFile`] that can be used to read the data.
+    ///
+    /// This flushes any buffered data to the file first.
+    pub fn into_file(self) -> Result<TdmsFile<F>, TdmsError> {
+        let file = self.writer.into_inner()?;
+        Ok(TdmsFile {
+            index: self.index,
+            file,
+            cache: None,
+        })
+    }
+}
+
+/// Write a data segment for the channels and add it to the index.
+fn write_channels<F: Write, W: TdmsWriter<F>, D: TdmsStorageType, C: AsRef<ChannelPath>>(
+    index: &mut Index,
+    writer: &mut W,
+    channels: &[C],
+    values: &[D],
+    layout: DataLayout,
+) -> Result<(), TdmsError> {
+    let stream = DataStreamWriter::new(index, writer, channels, values, layout)?;
+    stream.end_stream()?;
+    Ok(())
+}
+
+/// Write a meta data only segment for the properties and add it to the index.
+fn write_properties<F: Write, W: TdmsWriter<F>>(
+    index: &mut Index,
+    writer: &mut W,
+    path: &PropertyPath,
+    properties: &[(&str, PropertyValue)],
+) -> Result<(), TdmsError> {
+    let segment = write_properties_segment(writer, path, properties)?;
+    index.add_segment(segment)?;
+    Ok(())
+}
+
+/// Write a meta data only segment for the properties.
+///
+/// This returns the segment so the caller can add it to the index once it has been written.
+pub(super) fn write_properties_segment<F: Write, W: TdmsWriter<F>>(
+    writer: &mut W,
+    path: &PropertyPath,
+    properties: &[(&str, PropertyValue)],
+) -> Result<Segment, TdmsError> {
+    let path = path.path();
+    let properties = properties
+        .iter()
+        .map(|(name, value)| (name.to_string(), (*value).clone()))
+        .collect();
+
+    let object = ObjectMetaData {
+        path: path.to_string(),
+        properties,
+        raw_data_index: RawDataIndex::None,
+    };
+
+    let meta = MetaData {
+        objects: vec![object],
+    };
+
+    writer.write_segment(ToC::default(), Some(meta), Option::<&[u8]>::None)
 }
 
 struct DataStreamWriter<'a, F: Write, W: TdmsWriter<F>> {
@@ -114,42 +370,7 @@ impl<'a, F: Write, W: TdmsWriter<F>> DataStreamWriter<'a, F, W> {
         values: &'b [D],
         layout: DataLayout,
     ) -> Result<Self, TdmsError> {
-        let channel_count = NonZeroUsize::new(channels.len()).ok_or(TdmsError::NoChannels)?;
-        let raw_data = MultiChannelSlice::from_slice(values, channel_count)?;
-        let data_structures = raw_data
-            .data_structure()
-            .into_iter()
-            .map(DataFormat::RawData);
-
-        let channels = channels
-            .iter()
-            .map(|path| path.as_ref().path()) //surely a way to avoid this.
-            .zip(data_structures)
-            .collect();
-
-        let (matches_live, channels) = index.check_write_values(channels);
-
-        let meta = if matches_live {
-            None
-        } else {
-            let objects: Vec<ObjectMetaData> = channels
-                .into_iter()
-                .map(|(path, raw_index)| ObjectMetaData {
-                    path: path.to_string(),
-                    properties: vec![],
-                    raw_data_index: raw_index,
-                })
-                .collect();
-
-            Some(MetaData { objects })
-        };
-
-        let toc = ToC {
-            contains_new_object_list: !matches_live,
-            data_is_interleaved: layout == DataLayout::Interleaved,
-            ..Default::default()
-        };
-        let segment = writer.write_segment(toc, meta, Some(raw_data))?;
+        let segment = write_channels_segment(index, writer, channels, values, layout)?;
         Ok(Self {
             index,
             writer,
@@ -163,3 +384,61 @@ impl<'a, F: Write, W: TdmsWriter<F>> DataStreamWriter<'a, F, W> {
         Ok(())
     }
 }
+
+/// Write a data segment for the channels.
+///
+/// The index is used to decide what metadata is needed but isn't updated. This returns
+/// the segment so the caller can add it to the index once it has been written.
+pub(super) fn write_channels_segment<F: Write, W: TdmsWriter<F>, C, D>(
+    index: &Index,
+    writer: &mut W,
+    channels: &[C],
+    values: &[D],
+    layout: DataLayout,
+) -> Result<Segment, TdmsError>
+where
+    C: AsRef<ChannelPath>,
+    D: TdmsStorageType,
+{
+    let channel_count = NonZeroUsize::new(channels.len()).ok_or(TdmsError::NoChannels)?;
+    let raw_data = MultiChannelSlice::from_slice(values, channel_count)?;
+    let data_structures = raw_data
+        .data_structure()
+        .into_iter()
+        .map(DataFormat::RawData);
+
+    let channels = channels
+        .iter()
+        .map(|path| path.as_ref().path()) //surely a way to avoid this.
+        .zip(data_structures)
+        .collect();
+
+    let (matches_live, channels) = index.check_write_values(channels);
+    let formats_match = channels
+        .iter()
+        .all(|(_, raw_index)| *raw_index == RawDataIndex::MatchPrevious);
+
+    // We can only skip the meta data if nothing has changed.
+    // If just the formats change we can list those without a new object list.
+    let meta = if matches_live && formats_match {
+        None
+    } else {
+        let objects: Vec<ObjectMetaData> = channels
+            .into_iter()
+            .map(|(path, raw_index)| ObjectMetaData {
+                path: path.to_string(),
+                properties: vec![],
+                raw_data_index: raw_index,
+            })
+            .collect();
+
+        Some(MetaData { objects })
+    };
+
+    let toc = ToC {
+        contains_new_object_list: !matches_live,
+        data_is_interleaved: layout == DataLayout::Interleaved,
+        ..Default::default()
+    };
+    writer.write_segment(toc, meta, Some(raw_data))
+}
@@ -0,0 +1,631 @@
+//! Copy a file to a new stream leaving out or renaming selected objects and properties.
+//!
+//! TDMS has no way to delete or rename anything in an existing file so this rewrites it.
+//! The copy is made a segment at a time, rewriting the metadata and streaming the
+//! raw data from the source without the bytes of any removed channels, so the data
+//! blocks are never held in memory.
+
+use std::io::{Read, Seek, SeekFrom, Write};
+
+use super::TdmsFile;
+use super::rename::{apply_renames, check_renames};
+use crate::error::TdmsError;
+use crate::index::DataBlockInfo;
+use crate::io::writer::{BigEndianWriter, LittleEndianWriter, TdmsWriter};
+use crate::meta_data::{MetaData, ObjectMetaData, RawDataIndex, RawDataMeta, Segment, ToC};
+use crate::raw_data::WriteBlock;
+use crate::{ChannelPath, DataLayout, PropertyPath};
+
+/// The objects and properties to leave out of or rename in a copy made with
+/// [`TdmsFile::copy_filtered`].
+///
+/// Objects are matched by their names in the original file, so a removal still applies
+/// to an object which is also renamed.
+///
+/// # Example
+///
+/// ```rust
+/// use tedium::{ChannelPath, CopyFilter, PropertyPath};
+///
+/// let filter = CopyFilter::new()
+///     .remove_group("scratch")
+///     .remove_channel(&ChannelPath::new("group", "debug"))
+///     .remove_property(&PropertyPath::file(), "operator")
+///     .remove_property_everywhere("customer_id")
+///     .rename(&PropertyPath::group("Rig 1"), &PropertyPath::group("rig_1"));
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct CopyFilter {
+    groups: Vec<String>,
+    channels: Vec<ChannelPath>,
+    properties: Vec<(PropertyPath, String)>,
+    property_names: Vec<String>,
+    renames: Vec<(PropertyPath, PropertyPath)>,
+}
+
+impl CopyFilter {
+    /// Create a filter which copies everything.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Leave out the group with all of its channels.
+    pub fn remove_group(mut self, group: &str) -> Self {
+        self.groups.push(group.to_string());
+        self
+    }
+
+    /// Leave out the channel, including its data.
+    pub fn remove_channel(mut self, channel: &ChannelPath) -> Self {
+        self.channels.push(channel.clone());
+        self
+    }
+
+    /// Leave out the named property on the object.
+    pub fn remove_property(mut self, path: &PropertyPath, name: &str) -> Self {
+        self.properties.push((path.clone(), name.to_string()));
+        self
+    }
+
+    /// Leave out the named property on every object in the file.
+    pub fn remove_property_everywhere(mut self, name: &str) -> Self {
+        self.property_names.push(name.to_string());
+        self
+    }
+
+    /// Rename a group or channel.
+    ///
+    /// Renaming a group moves all of its channels to the new group. Both paths must be
+    /// to the same kind of object. Renames are applied in the order they are added.
+    pub fn rename(mut self, from: &PropertyPath, to: &PropertyPath) -> Self {
+        self.renames.push((from.clone(), to.clone()));
+        self
+    }
+
+    pub(super) fn renames(&self) -> &[(PropertyPath, PropertyPath)] {
+        &self.renames
+    }
+
+    /// Get the path to write for the object at `path` in the original file.
+    pub(super) fn output_path(&self, path: &str) -> String {
+        apply_renames(path, &self.renames).0
+    }
+
+    fn removes_object(&self, path: &str) -> bool {
+        // Paths we can't parse can't be named in the filter so are always kept.
+        let Ok(path) = PropertyPath::try_from(path) else {
+            return false;
+        };
+        match (path.group_name(), path.channel_name()) {
+            (Some(group), channel) => {
+                self.groups.iter().any(|removed| removed == group)
+                    || (channel.is_some()
+                        && self
+                            .channels
+                            .iter()
+                            .any(|removed| removed.path() == path.path()))
+            }
+            (None, _) => false,
+        }
+    }
+
+    fn removes_property(&self, path: &str, name: &str) -> bool {
+        self.property_names.iter().any(|removed| removed == name)
+            || self
+                .properties
+                .iter()
+                .any(|(removed_path, removed)| removed_path.path() == path && removed == name)
+    }
+}
+
+impl<F: Read + Seek> TdmsFile<F> {
+    /// Write a copy of the file to `output` without the objects and properties in the filter.
+    ///
+    /// Each segment is rewritten in the same byte order and layout, so the copy has
+    /// the same segment structure except that segments left with nothing in them are
+    /// dropped. Channel data is copied as raw bytes without decoding it.
+    ///
+    /// Channels can't be removed from data blocks which contain variable size data
+    /// such as strings. This returns [`TdmsError::VariableSizeChannelRemoval`] instead.
+    ///
+    /// Returns [`TdmsError::InvalidRename`] if a rename would give two objects the same path.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use tedium::{ChannelPath, CopyFilter, DataLayout, PropertyPath, PropertyValue, TdmsFile};
+    ///
+    /// let mut file = TdmsFile::new(std::io::Cursor::new(vec![])).unwrap();
+    /// let mut writer = file.writer().unwrap();
+    /// writer
+    ///     .write_properties(
+    ///         &PropertyPath::file(),
+    ///         &[("customer", PropertyValue::String("ACME".to_string()))],
+    ///     )
+    ///     .unwrap();
+    /// writer
+    ///     .write_channels(
+    ///         &[ChannelPath::new("group", "keep"), ChannelPath::new("group", "secret")],
+    ///         &[1.0, 2.0],
+    ///         DataLayout::Interleaved,
+    ///     )
+    ///     .unwrap();
+    /// drop(writer);
+    ///
+    /// let filter = CopyFilter::new()
+    ///     .remove_property(&PropertyPath::file(), "customer")
+    ///     .remove_channel(&ChannelPath::new("group", "secret"));
+    /// let mut copy = std::io::Cursor::new(vec![]);
+    /// file.copy_filtered(&mut copy, &filter).unwrap();
+    ///
+    /// let copy = TdmsFile::new(copy).unwrap();
+    /// assert_eq!(copy.read_property(&PropertyPath::file(), "customer").unwrap(), None);
+    /// assert_eq!(copy.channel_length(&ChannelPath::new("group", "secret")), None);
+    /// assert_eq!(copy.channel_length(&ChannelPath::new("group", "keep")), Some(1));
+    /// ```
+    pub fn copy_filtered(
+        &mut self,
+        mut output: impl Write,
+        filter: &CopyFilter,
+    ) -> Result<(), TdmsError> {
+        check_renames(&self.index, filter.renames())?;
+
+        // The data channels of the last segment we wrote so we can skip repeating
+        // the metadata when it hasn't changed.
+        let mut previous_channels: Option<Vec<(String, RawDataMeta)>> = None;
+
+        for segment_index in 0..self.index.segments().len() {
+            let (offset, data_block) = {
+                let segment = &self.index.segments()[segment_index];
+                (segment.offset, segment.data_block)
+            };
+            self.file.seek(SeekFrom::Start(offset))?;
+            let source = Segment::read(&mut self.file)?;
+            let source_objects = source
+                .meta_data
+                .map(|meta| meta.objects)
+                .unwrap_or_default();
+
+            let block = data_block.and_then(|block| self.index.data_block_info(block));
+            let (channels, copy) = match block {
+                Some(block) => {
+                    let keep: Vec<bool> = block
+                        .channels
+                        .iter()
+                        .map(|channel| !filter.removes_object(&channel.path))
+                        .collect();
+                    // The final segment may have been cut short.
+                    let available = self
+                        .file
+                        .seek(SeekFrom::End(0))?
+                        .saturating_sub(block.start)
+                        .min(block.length);
+                    let copy = BlockCopy::new(&block, keep.clone(), available)?;
+                    if copy.output_length() == 0 {
+                        // Nothing to copy so only the metadata is kept.
+                        (Vec::new(), None)
+                    } else {
+                        let channels: Vec<(String, RawDataMeta)> = block
+                            .channels
+                            .into_iter()
+                            .zip(keep)
+                            .filter(|(_, keep)| *keep)
+                            .map(|(channel, _)| (channel.path, channel.format))
+                            .collect();
+                        (channels, Some(copy))
+                    }
+                }
+                None => (Vec::new(), None),
+            };
+
+            let mut objects = Vec::new();
+            let mut channel_properties = vec![Vec::new(); channels.len()];
+            for mut object in source_objects {
+                if filter.removes_object(&object.path) {
+                    continue;
+                }
+                object
+                    .properties
+                    .retain(|(name, _)| !filter.removes_property(&object.path, name));
+
+                match channels.iter().position(|(path, _)| *path == object.path) {
+                    Some(position) => channel_properties[position] = object.properties,
+                    None => objects.push(ObjectMetaData {
+                        path: filter.output_path(&object.path),
+                        raw_data_index: RawDataIndex::None,
+                        ..object
+                    }),
+                }
+            }
+
+            let toc = ToC {
+                data_is_interleaved: source.toc.data_is_interleaved,
+                ..Default::default()
+            };
+            if channels.is_empty() {
+                if objects.is_empty() {
+                    continue;
+                }
+                write_segment(
+                    &mut output,
+                    source.toc.big_endian,
+                    toc,
+                    Some(MetaData { objects }),
+                    None,
+                )?;
+                continue;
+            }
+
+            let unchanged = objects.is_empty()
+                && channel_properties.iter().all(Vec::is_empty)
+                && previous_channels.as_ref() == Some(&channels);
+            let meta = if unchanged {
+                None
+            } else {
+                objects.extend(channels.iter().zip(channel_properties).map(
+                    |((path, format), properties)| ObjectMetaData {
+                        path: filter.output_path(path),
+                        properties,
+                        raw_data_index: RawDataIndex::RawData(format.clone()),
+                    },
+                ));
+                Some(MetaData { objects })
+            };
+            let toc = ToC {
+                contains_new_object_list: meta.is_some(),
+                ..toc
+            };
+            // Channels are only kept if they have data in the block so this is always set.
+            let Some(copy) = copy else {
+                continue;
+            };
+            write_segment(
+                &mut output,
+                source.toc.big_endian,
+                toc,
+                meta,
+                Some(DataLength(copy.output_length())),
+            )?;
+            copy.copy(&mut self.file, &mut output)?;
+            previous_channels = Some(channels);
+        }
+
+        output.flush()?;
+        Ok(())
+    }
+}
+
+/// The size of the buffer used to remove channels from interleaved data.
+const COPY_BUFFER_BYTES: usize = 64 * 1024;
+
+/// A plan for copying the raw data of the kept channels in a block.
+///
+/// Any partial chunk at the end of the block is dropped unless every channel is kept.
+enum BlockCopy {
+    /// Copy this many bytes from the start of the block unchanged.
+    All { start: u64, length: u64 },
+    /// Copy these ranges of the file, one after the other.
+    Ranges(Vec<(u64, u64)>),
+    /// Copy the kept columns of each row.
+    Rows {
+        start: u64,
+        rows: u64,
+        row_size: usize,
+        /// The offset and size of each kept value in the row.
+        columns: Vec<(usize, usize)>,
+    },
+}
+
+impl BlockCopy {
+    fn new(block: &DataBlockInfo, keep: Vec<bool>, available: u64) -> Result<Self, TdmsError> {
+        if keep.iter().all(|keep| *keep) {
+            return Ok(Self::All {
+                start: block.start,
+                length: available,
+            });
+        }
+
+        // Byte widths of a single value of each channel.
+        let mut value_sizes = Vec::with_capacity(block.channels.len());
+        for channel in &block.channels {
+            let size = channel.format.data_type.size() as u64;
+            if channel.format.total_size_bytes.is_some() || size == 0 {
+                return Err(TdmsError::VariableSizeChannelRemoval(channel.path.clone()));
+            }
+            value_sizes.push(size);
+        }
+        let chunk_size: u64 = block
+            .channels
+            .iter()
+            .zip(&value_sizes)
+            .map(|(channel, size)| channel.format.number_of_values * size)
+            .sum();
+        let chunks = (block.number_of_chunks as u64).min(available / chunk_size.max(1));
+
+        match block.layout {
+            DataLayout::Contigious => {
+                let mut ranges: Vec<(u64, u64)> = Vec::new();
+                for chunk in 0..chunks {
+                    let mut position = block.start + chunk * chunk_size;
+                    for ((channel, size), keep) in
+                        block.channels.iter().zip(&value_sizes).zip(&keep)
+                    {
+                        let length = channel.format.number_of_values * size;
+                        if *keep {
+                            // Merge with the previous range if they touch.
+                            match ranges.last_mut() {
+                                Some((start, previous)) if *start + *previous == position => {
+                                    *previous += length
+                                }
+                                _ => ranges.push((position, length)),
+                            }
+                        }
+                        position += length;
+                    }
+                }
+                Ok(Self::Ranges(ranges))
+            }
+            DataLayout::Interleaved => {
+                let rows = block
+                    .channels
+                    .first()
+                    .map_or(0, |channel| channel.format.number_of_values);
+                if block
+                    .channels
+                    .iter()
+                    .any(|channel| channel.format.number_of_values != rows)
+                {
+                    return Err(TdmsError::InterleavedLengthMismatch);
+                }
+                let mut columns = Vec::new();
+                let mut offset = 0;
+                for (size, keep) in value_sizes.iter().zip(&keep) {
+                    if *keep {
+                        columns.push((offset, *size as usize));
+                    }
+                    offset += *size as usize;
+                }
+                Ok(Self::Rows {
+                    start: block.start,
+                    rows: rows * chunks,
+                    row_size: offset,
+                    columns,
+                })
+            }
+        }
+    }
+
+    /// The number of bytes this writes to the output.
+    fn output_length(&self) -> u64 {
+        match self {
+            Self::All { length, .. } => *length,
+            Self::Ranges(ranges) => ranges.iter().map(|(_, length)| length).sum(),
+            Self::Rows { rows, columns, .. } => {
+                rows * columns.iter().map(|(_, size)| *size as u64).sum::<u64>()
+            }
+        }
+    }
+
+    /// Copy the data from the source to the output through a fixed size buffer.
+    fn copy(
+        &self,
+        source: &mut (impl Read + Seek),
+        output: &mut impl Write,
+    ) -> Result<(), TdmsError> {
+        match self {
+            Self::All { start, length } => copy_range(source, output, *start, *length),
+            Self::Ranges(ranges) => {
+                for (start, length) in ranges {
+                    copy_range(source, output, *start, *length)?;
+                }
+                Ok(())
+            }
+            Self::Rows {
+                start,
+                rows,
+                row_size,
+                columns,
+            } => {
+                source.seek(SeekFrom::Start(*start))?;
+                let rows_per_batch = (COPY_BUFFER_BYTES / row_size).max(1);
+                let mut input = vec![0u8; rows_per_batch * row_size];
+                let mut kept = Vec::with_capacity(input.len());
+                let mut remaining = *rows;
+                while remaining > 0 {
+                    let batch = (rows_per_batch as u64).min(remaining) as usize;
+                    let input = &mut input[..batch * row_size];
+                    source.read_exact(input)?;
+                    kept.clear();
+                    for row in input.chunks_exact(*row_size) {
+                        for (offset, size) in columns {
+                            kept.extend_from_slice(&row[*offset..*offset + *size]);
+                        }
+                    }
+                    output.write_all(&kept)?;
+                    remaining -= batch as u64;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+fn copy_range(
+    source: &mut (impl Read + Seek),
+    output: &mut impl Write,
+    start: u64,
+    length: u64,
+) -> Result<(), TdmsError> {
+    source.seek(SeekFrom::Start(start))?;
+    let copied = std::io::copy(&mut source.take(length), output)?;
+    if copied < length {
+        return Err(TdmsError::EndOfFile);
+    }
+    Ok(())
+}
+
+/// Stands in for raw data of a known length so the segment header can be written
+/// before the data is copied.
+struct DataLength(u64);
+
+impl WriteBlock for DataLength {
+    fn data_structure(&self) -> Vec<RawDataMeta> {
+        Vec::new()
+    }
+
+    fn write<W: Write, T: TdmsWriter<W>>(&self, _writer: &mut T) -> Result<(), TdmsError> {
+        Ok(())
+    }
+
+    fn size(&self) -> usize {
+        self.0 as usize
+    }
+}
+
+fn write_segment(
+    output: &mut impl Write,
+    big_endian: bool,
+    toc: ToC,
+    meta: Option<MetaData>,
+    data: Option<DataLength>,
+) -> Result<(), TdmsError> {
+    if big_endian {
+        let mut writer = BigEndianWriter::from_writer(output);
+        writer.write_segment(toc, meta, data)?;
+        writer.into_inner()?;
+    } else {
+        let mut writer = LittleEndianWriter::from_writer(output);
+        writer.write_segment(toc, meta, data)?;
+        writer.into_inner()?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use super::*;
+    use crate::PropertyValue;
+
+    fn channels() -> [ChannelPath; 3] {
+        [
+            ChannelPath::new("group", "ch1"),
+            ChannelPath::new("group", "ch2"),
+            ChannelPath::new("other", "ch3"),
+        ]
+    }
+
+    fn build_file(layout: DataLayout) -> TdmsFile<Cursor<Vec<u8>>> {
+        let mut file = TdmsFile::new(Cursor::new(Vec::new())).unwrap();
+        let mut writer = file.writer().unwrap();
+        writer
+            .write_properties(
+                &PropertyPath::channel("group", "ch2"),
+                &[
+                    ("unit", PropertyValue::String("V".to_string())),
+                    ("secret", PropertyValue::I32(1)),
+                ],
+            )
+            .unwrap();
+        for block in 0..3 {
+            let values: Vec<i32> = (0..30).map(|i| block * 100 + i).collect();
+            writer.write_channels(&channels(), &values, layout).unwrap();
+        }
+        drop(writer);
+        file
+    }
+
+    fn copy(
+        file: &mut TdmsFile<Cursor<Vec<u8>>>,
+        filter: &CopyFilter,
+    ) -> TdmsFile<Cursor<Vec<u8>>> {
+        let mut output = Cursor::new(Vec::new());
+        file.copy_filtered(&mut output, filter).unwrap();
+        TdmsFile::new(output).unwrap()
+    }
+
+    fn read(file: &mut TdmsFile<Cursor<Vec<u8>>>, channel: &ChannelPath) -> Vec<i32> {
+        let mut output = vec![0; file.channel_length(channel).unwrap() as usize];
+        file.read_channel(channel, &mut output).unwrap();
+        output
+    }
+
+    #[test]
+    fn empty_filter_copies_everything() {
+        let mut file = build_file(DataLayout::Interleaved);
+        let mut copy = copy(&mut file, &CopyFilter::new());
+        for channel in channels() {
+            assert_eq!(read(&mut copy, &channel), read(&mut file, &channel));
+        }
+        assert_eq!(copy.file.get_ref(), file.file.get_ref());
+    }
+
+    #[test]
+    fn removes_channel_data() {
+        for layout in [DataLayout::Contigious, DataLayout::Interleaved] {
+            let mut file = build_file(layout);
+            let removed = ChannelPath::new("group", "ch2");
+            let mut copy = copy(&mut file, &CopyFilter::new().remove_channel(&removed));
+
+            assert_eq!(copy.channel_length(&removed), None);
+            for channel in [&channels()[0], &channels()[2]] {
+                assert_eq!(read(&mut copy, channel), read(&mut file, channel));
+            }
+            // The property segment only had the removed channel in it.
+            assert_eq!(copy.segments().unwrap().len(), 3);
+        }
+    }
+
+    #[test]
+    fn drops_partial_chunk_when_removing_channels() {
+        let file = build_file(DataLayout::Contigious);
+        let mut bytes = file.file.into_inner();
+        bytes.truncate(bytes.len() - 4);
+        let mut file = TdmsFile::new(Cursor::new(bytes)).unwrap();
+
+        let removed = ChannelPath::new("group", "ch2");
+        let mut copy = copy(&mut file, &CopyFilter::new().remove_channel(&removed));
+        let channel = ChannelPath::new("group", "ch1");
+        let expected: Vec<i32> = (0..10).chain(100..110).collect();
+        assert_eq!(read(&mut copy, &channel), expected);
+    }
+
+    #[test]
+    fn removes_group_and_properties() {
+        let mut file = build_file(DataLayout::Contigious);
+        let filter = CopyFilter::new()
+            .remove_group("other")
+            .remove_property_everywhere("secret");
+        let mut copy = copy(&mut file, &filter);
+
+        let groups: Vec<_> = copy.list_groups().collect();
+        assert_eq!(groups, ["group"]);
+        let path = PropertyPath::channel("group", "ch2");
+        assert_eq!(copy.read_property(&path, "secret").unwrap(), None);
+        assert_eq!(
+            copy.read_property(&path, "unit").unwrap(),
+            Some(&PropertyValue::String("V".to_string()))
+        );
+        let channel = ChannelPath::new("group", "ch1");
+        assert_eq!(read(&mut copy, &channel), read(&mut file, &channel));
+    }
+
+    #[test]
+    fn keeps_big_endian_segments() {
+        let mut file = TdmsFile::new(Cursor::new(Vec::new())).unwrap();
+        let mut writer = file.big_endian_writer().unwrap();
+        writer
+            .write_channels(&channels(), &[1u16, 2, 3, 4, 5, 6], DataLayout::Interleaved)
+            .unwrap();
+        drop(writer);
+
+        let filter = CopyFilter::new().remove_channel(&ChannelPath::new("group", "ch1"));
+        let mut copy = copy(&mut file, &filter);
+        assert!(copy.segments().unwrap()[0].toc.big_endian);
+        let mut output = [0u16; 2];
+        copy.read_channel(&ChannelPath::new("other", "ch3"), &mut output)
+            .unwrap();
+        assert_eq!(output, [3, 6]);
+    }
+}
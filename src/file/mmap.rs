@@ -0,0 +1,374 @@
+//! A memory mapped TDMS file.
+//!
+//! With the file mapped into memory we can copy channel data straight into the output
+//! when it is already in the right format, or borrow it without copying at all.
+
+use std::any::TypeId;
+use std::fs::File;
+use std::io::Cursor;
+use std::path::Path;
+
+use memmap2::Mmap;
+
+use super::channel_reader::{BlockRead, plan_channels_read};
+use crate::error::TdmsError;
+use crate::index::Index;
+use crate::io::data_types::TdmsStorageType;
+use crate::raw_data::{BlockReadChannelConfig, ChunkSize, DataBlock, DataLayout, Endianess};
+use crate::{ChannelPath, PropertyPath, PropertyValue};
+
+#[cfg(target_endian = "little")]
+const NATIVE_ENDIAN: Endianess = Endianess::Little;
+#[cfg(target_endian = "big")]
+const NATIVE_ENDIAN: Endianess = Endianess::Big;
+
+/// A read only TDMS file accessed through a memory map.
+///
+/// When a channel is stored contiguously in the native byte order with the same type
+/// as the output, reads are a straight copy from the map. Other data falls back to
+/// the standard decoding.
+///
+/// [`Self::channel_slices`] can borrow the channel data directly from the map.
+///
+/// Reads only need `&self` so this can be shared between threads.
+///
+/// # Safety of the mapping
+///
+/// The file must not be truncated or modified by this or another process while it
+/// is mapped. Doing so can crash the process or change the data behind the slices
+/// returned by [`Self::channel_slices`].
+#[derive(Debug)]
+pub struct MmapTdmsFile {
+    index: Index,
+    map: Mmap,
+}
+
+impl MmapTdmsFile {
+    /// Map the file from the path and index the metadata.
+    pub fn open(path: &Path) -> Result<Self, TdmsError> {
+        let file = File::options().read(true).open(path)?;
+        // SAFETY: The caller is responsible for the file not being modified while mapped.
+        // This is documented on the type.
+        let map = unsafe { Mmap::map(&file)? };
+        let index = super::build_index(&mut Cursor::new(&map[..]))?;
+        Ok(Self { index, map })
+    }
+
+    /// Get the length of the channel.
+    pub fn channel_length(&self, channel: &ChannelPath) -> Option<u64> {
+        self.index.channel_length(channel)
+    }
+
+    /// Read the property by name from the full object path.
+    /// This will return `None` if the property does not exist.
+    pub fn read_property(
+        &self,
+        object_path: &PropertyPath,
+        property: &str,
+    ) -> Result<Option<&PropertyValue>, TdmsError> {
+        self.index.get_object_property(object_path, property)
+    }
+
+    /// Read a single channel from the file.
+    ///
+    /// See [`crate::TdmsFile::read_channel`].
+    pub fn read_channel<D: TdmsStorageType>(
+        &self,
+        channel: &ChannelPath,
+        output: &mut [D],
+    ) -> Result<(), TdmsError> {
+        self.read_channel_from(channel, 0, output)
+    }
+
+    /// Read a single channel from the file starting at a specific sample position.
+    ///
+    /// See [`crate::TdmsFile::read_channel_from`].
+    pub fn read_channel_from<D: TdmsStorageType>(
+        &self,
+        channel: &ChannelPath,
+        start: u64,
+        output: &mut [D],
+    ) -> Result<(), TdmsError> {
+        self.read_channels_from(&[channel], start, &mut [output])
+    }
+
+    /// Read multiple channels from the file.
+    ///
+    /// See [`crate::TdmsFile::read_channels`].
+    pub fn read_channels<D: TdmsStorageType>(
+        &self,
+        channels: &[impl AsRef<ChannelPath>],
+        output: &mut [&mut [D]],
+    ) -> Result<(), TdmsError> {
+        self.read_channels_from(channels, 0, output)
+    }
+
+    /// Read multiple channels from the file starting at a specific sample position.
+    ///
+    /// See [`crate::TdmsFile::read_channels_from`].
+    pub fn read_channels_from<D: TdmsStorageType>(
+        &self,
+        channels: &[impl AsRef<ChannelPath>],
+        start: u64,
+        output: &mut [&mut [D]],
+    ) -> Result<(), TdmsError> {
+        let plan = plan_channels_read(&self.index, channels, start)?;
+        self.execute_read_plan(plan, output)
+    }
+
+    /// Borrow the channel data directly from the map without copying.
+    ///
+    /// This returns a slice for each contiguous run of the channel in the file. That is
+    /// at least one per data block but may be more where a block contains multiple writes.
+    ///
+    /// This is only possible when the data is stored contiguously in the native byte order,
+    /// with the same type as `D` and is aligned in the file for `D`. Otherwise it returns
+    /// [`TdmsError::ZeroCopyNotPossible`] and you should use [`Self::read_channel`] instead.
+    pub fn channel_slices<D: TdmsStorageType>(
+        &self,
+        channel: &ChannelPath,
+    ) -> Result<Vec<&[D]>, TdmsError> {
+        if !is_plain_data::<D>() {
+            return Err(TdmsError::ZeroCopyNotPossible(
+                "the output type is not a plain numeric type",
+            ));
+        }
+        let locations = self
+            .index
+            .get_channel_data_positions(channel)
+            .ok_or_else(|| TdmsError::MissingObject(channel.path().to_owned()))?;
+
+        let mut slices = Vec::new();
+        for location in locations {
+            let block = self.data_block(location.data_block)?;
+            check_type::<D>(block, location.channel_index)?;
+            let runs = ChannelRuns::new(block, location.channel_index)?.ok_or(
+                TdmsError::ZeroCopyNotPossible(
+                    "the data is interleaved, variable size or not in the native byte order",
+                ),
+            )?;
+            for chunk in 0..runs.chunks {
+                let bytes = self.run_bytes::<D>(&runs, chunk, 0, runs.values_per_chunk)?;
+                if bytes.as_ptr().align_offset(std::mem::align_of::<D>()) != 0 {
+                    return Err(TdmsError::ZeroCopyNotPossible(
+                        "the data is not aligned for the type",
+                    ));
+                }
+                // SAFETY: D is a plain numeric type so any bit pattern is valid,
+                // the pointer is aligned and the length is a whole number of values.
+                let values = unsafe {
+                    std::slice::from_raw_parts(
+                        bytes.as_ptr() as *const D,
+                        bytes.len() / D::SIZE_BYTES,
+                    )
+                };
+                slices.push(values);
+            }
+        }
+        Ok(slices)
+    }
+
+    fn data_block(&self, block: usize) -> Result<&DataBlock, TdmsError> {
+        self.index
+            .get_data_block(block)
+            .ok_or_else(|| TdmsError::DataBlockNotFound(ChannelPath::new("MIXED", "MIXED"), block))
+    }
+
+    /// Get the bytes in the map for `count` values of the run starting at `skip`.
+    ///
+    /// The positions come from the file headers so any overflow means the run is
+    /// past the end of the map.
+    fn run_bytes<D: TdmsStorageType>(
+        &self,
+        runs: &ChannelRuns,
+        chunk: u64,
+        skip: u64,
+        count: u64,
+    ) -> Result<&[u8], TdmsError> {
+        let size = D::SIZE_BYTES as u64;
+        let start = chunk
+            .checked_mul(runs.chunk_size)
+            .and_then(|offset| offset.checked_add(runs.first_run))
+            .and_then(|start| start.checked_add(skip.checked_mul(size)?))
+            .ok_or(TdmsError::EndOfFile)?;
+        let end = count
+            .checked_mul(size)
+            .and_then(|length| start.checked_add(length))
+            .ok_or(TdmsError::EndOfFile)?;
+        let start = usize::try_from(start).map_err(|_| TdmsError::EndOfFile)?;
+        let end = usize::try_from(end).map_err(|_| TdmsError::EndOfFile)?;
+        self.map.get(start..end).ok_or(TdmsError::EndOfFile)
+    }
+
+    /// Execute a read plan, copying directly from the map where possible.
+    fn execute_read_plan<D: TdmsStorageType>(
+        &self,
+        plan: Vec<BlockRead>,
+        output: &mut [&mut [D]],
+    ) -> Result<(), TdmsError> {
+        // The unread part of each output. We split off the part for each block as we go.
+        let mut remaining: Vec<&mut [D]> =
+            output.iter_mut().map(|output| &mut output[..]).collect();
+
+        for location in plan {
+            if remaining.iter().all(|output| output.is_empty()) {
+                break;
+            }
+
+            let block = self.data_block(location.data_block)?;
+            let chunks = block.number_of_chunks()? as u64;
+            let mut fallback = Vec::new();
+
+            for (plan, output) in location.channel_indexes.iter().zip(remaining.iter_mut()) {
+                let Some(plan) = plan else {
+                    continue;
+                };
+                let meta = block.channels.get(plan.index).ok_or_else(|| {
+                    TdmsError::DataBlockNotFound(
+                        ChannelPath::new("MIXED", "MIXED"),
+                        location.data_block,
+                    )
+                })?;
+                let available = meta
+                    .number_of_values
+                    .checked_mul(chunks)
+                    .ok_or(TdmsError::ChunkSizeOverflow)?
+                    .saturating_sub(plan.samples_to_skip);
+                let count = usize::try_from(available)
+                    .map_or(output.len(), |available| output.len().min(available));
+                if count == 0 {
+                    continue;
+                }
+                let (block_output, rest) = std::mem::take(output).split_at_mut(count);
+                *output = rest;
+
+                let runs = if is_plain_data::<D>() && check_type::<D>(block, plan.index).is_ok() {
+                    ChannelRuns::new(block, plan.index)?
+                } else {
+                    None
+                };
+
+                match runs {
+                    Some(runs) => self.copy_runs(&runs, plan.samples_to_skip, block_output)?,
+                    None => fallback.push(BlockReadChannelConfig {
+                        channel_index: plan.index,
+                        samples_to_skip: plan.samples_to_skip,
+                        output: block_output,
+                    }),
+                }
+            }
+
+            if !fallback.is_empty() {
+                block.read_with_per_channel_skip(&mut Cursor::new(&self.map[..]), &mut fallback)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Copy the channel from the runs into the output.
+    fn copy_runs<D: TdmsStorageType>(
+        &self,
+        runs: &ChannelRuns,
+        mut skip: u64,
+        output: &mut [D],
+    ) -> Result<(), TdmsError> {
+        let mut written = 0;
+        for chunk in 0..runs.chunks {
+            if written == output.len() {
+                break;
+            }
+            if skip >= runs.values_per_chunk {
+                skip -= runs.values_per_chunk;
+                continue;
+            }
+            let wanted = output.len() - written;
+            let count = usize::try_from(runs.values_per_chunk - skip)
+                .map_or(wanted, |available| wanted.min(available));
+            let bytes = self.run_bytes::<D>(runs, chunk, skip, count as u64)?;
+            let target = &mut output[written..written + count];
+            // SAFETY: D is a plain numeric type so any bit pattern is valid and the
+            // byte slice covers exactly the values in the target.
+            unsafe {
+                std::ptr::copy_nonoverlapping(
+                    bytes.as_ptr(),
+                    target.as_mut_ptr() as *mut u8,
+                    bytes.len(),
+                );
+            }
+            written += count;
+            skip = 0;
+        }
+        Ok(())
+    }
+}
+
+/// Where a channel's values are in a contiguous data block.
+struct ChannelRuns {
+    /// The file position of the channel values in the first chunk.
+    first_run: u64,
+    values_per_chunk: u64,
+    chunk_size: u64,
+    chunks: u64,
+}
+
+impl ChannelRuns {
+    /// Locate the channel in the block if it can be copied directly.
+    ///
+    /// Returns `None` if the data is not contiguous, fixed size and in the native byte order.
+    fn new(block: &DataBlock, channel_index: usize) -> Result<Option<Self>, TdmsError> {
+        let contiguous = block.layout == DataLayout::Contigious || block.channels.len() == 1;
+        if !contiguous || block.byte_order != NATIVE_ENDIAN {
+            return Ok(None);
+        }
+        let ChunkSize::Fixed(chunk_size) = block.chunk_size()? else {
+            return Ok(None);
+        };
+
+        // chunk_size has already checked this sum can't overflow.
+        let channel_offset: u64 = block.channels[..channel_index]
+            .iter()
+            .map(|channel| channel.number_of_values * channel.data_type.size() as u64)
+            .sum();
+
+        Ok(Some(Self {
+            first_run: block
+                .start
+                .checked_add(channel_offset)
+                .ok_or(TdmsError::EndOfFile)?,
+            values_per_chunk: block.channels[channel_index].number_of_values,
+            chunk_size,
+            chunks: block.number_of_chunks()? as u64,
+        }))
+    }
+}
+
+/// Check the channel type is the natural type of `D`, so the bytes can be used directly.
+fn check_type<D: TdmsStorageType>(
+    block: &DataBlock,
+    channel_index: usize,
+) -> Result<(), TdmsError> {
+    let data_type = block.channels[channel_index].data_type;
+    if D::supports_data_type(&data_type) && data_type.size() as usize == D::SIZE_BYTES {
+        Ok(())
+    } else {
+        Err(TdmsError::DataTypeMismatch(data_type, D::NATURAL_TYPE))
+    }
+}
+
+/// Whether `D` is a primitive numeric type where any bytes are a valid value.
+fn is_plain_data<D: 'static>() -> bool {
+    [
+        TypeId::of::<i8>(),
+        TypeId::of::<u8>(),
+        TypeId::of::<i16>(),
+        TypeId::of::<u16>(),
+        TypeId::of::<i32>(),
+        TypeId::of::<u32>(),
+        TypeId::of::<i64>(),
+        TypeId::of::<u64>(),
+        TypeId::of::<f32>(),
+        TypeId::of::<f64>(),
+    ]
+    .contains(&TypeId::of::<D>())
+}
@@ -1,7 +1,28 @@
 //! The file module provides the public API for a TDMS file.
 
+#[cfg(feature = "tokio")]
+mod async_reader;
+#[cfg(feature = "tokio")]
+mod async_writer;
+mod background_writer;
+mod block_cache;
+#[cfg(any(feature = "parallel", feature = "tokio"))]
+mod block_pieces;
 mod channel_reader;
+mod durability;
 mod file_writer;
+mod filtered_copy;
+#[cfg(feature = "mmap")]
+mod mmap;
+#[cfg(feature = "parallel")]
+mod parallel_reader;
+mod raw_segment;
+mod read_only;
+mod rename;
+#[cfg(any(unix, windows))]
+mod shared_reader;
+mod storage;
+mod structure;
 
 use std::{
     fs::File,
@@ -9,38 +30,101 @@ use std::{
     path::Path,
 };
 
+use crate::index::{
+    ChannelInfo, DataBlockInfo, DataLocation, PropertyChange, PropertyPredicate, SegmentInfo,
+};
 use crate::meta_data::Segment;
 use crate::{ChannelPath, index::Index};
 use crate::{PropertyPath, PropertyValue, error::TdmsError};
 use crate::{
-    io::writer::{LittleEndianWriter, TdmsWriter},
+    io::writer::{BigEndianWriter, LittleEndianWriter, TdmsWriter},
     paths::path_group_name,
 };
-pub use file_writer::TdmsFileWriter;
+#[cfg(feature = "tokio")]
+pub use async_reader::AsyncTdmsFile;
+#[cfg(feature = "tokio")]
+pub use async_writer::AsyncTdmsFileWriter;
+pub use background_writer::{BackgroundWriter, FlushHandle, WriteQueue};
+use block_cache::BlockCache;
+pub use block_cache::BlockCacheStats;
+pub use durability::{SyncPolicy, SyncToDisk};
+pub use file_writer::{TdmsFileWriter, TdmsWriterHandle};
+pub use filtered_copy::CopyFilter;
+#[cfg(feature = "mmap")]
+pub use mmap::MmapTdmsFile;
+pub use raw_segment::RawDataBlock;
+pub use read_only::ReadOnly;
+#[cfg(any(unix, windows))]
+pub use shared_reader::SharedTdmsFile;
+#[cfg(any(unix, windows))]
+pub use storage::FileStorage;
+pub use storage::{MemoryStorage, StorageStream, StorageTdmsFile, TdmsStorage};
+pub use structure::{ChannelStructure, FileStructure, GroupStructure};
 
 /// A TDMS file.
 ///
 /// This is the main entry point for reading and writing TDMS files.
 ///
 /// To read a file use [`Self::load`]. This will load from the path and index the metadata ready for access.
+/// If you only need to read the file use [`Self::open_read_only`] instead.
 ///
 /// To create a new file use [`Self::create`]. This will replace any existing file at the path.
+/// To add to an existing file use [`Self::open_append`].
+///
+/// The `_locked` variants of these methods take an advisory lock on the file so that
+/// multiple processes don't write to the same file at once.
 ///
 /// To write to a file use [`Self::writer`]. This will return a writer that can be used to write data to the file.
+/// Data is written little endian by default. Use [`Self::big_endian_writer`] for big endian segments.
 #[derive(Debug)]
 pub struct TdmsFile<F: Read + Seek> {
     index: Index,
     file: F,
+    cache: Option<BlockCache>,
 }
 
 impl TdmsFile<File> {
     /// Load the file from the path. This step will load and index the metadata
     /// ready for access.
+    ///
+    /// The file is opened for reading and writing. Use [`Self::open_read_only`]
+    /// if you only need to read the file.
     pub fn load(path: &Path) -> Result<Self, TdmsError> {
         let file = File::options().read(true).write(true).open(path)?;
         Self::new(file)
     }
 
+    /// Load the file from the path holding an exclusive advisory lock.
+    ///
+    /// See [`Self::open_append_locked`] for details of the locking.
+    pub fn load_locked(path: &Path) -> Result<Self, TdmsError> {
+        let file = File::options().read(true).write(true).open(path)?;
+        lock_file(&file, LockKind::Exclusive)?;
+        Self::new(file)
+    }
+
+    /// Open the file ready to append new data, creating it if it doesn't exist.
+    ///
+    /// Existing data is indexed and the file is positioned at the end for writing.
+    pub fn open_append(path: &Path) -> Result<Self, TdmsError> {
+        let file = Self::append_options().open(path)?;
+        Self::new_at_end(file)
+    }
+
+    /// Open the file ready to append new data holding an exclusive advisory lock.
+    ///
+    /// The lock is taken before the file is indexed and is held until the file is dropped.
+    /// If another tedium process holds a lock on the file this returns [`TdmsError::FileLocked`]
+    /// rather than waiting.
+    ///
+    /// The lock is advisory so it only protects against other processes which also lock
+    /// the file, such as other tedium instances using the `_locked` methods.
+    pub fn open_append_locked(path: &Path) -> Result<Self, TdmsError> {
+        let file = Self::append_options().open(path)?;
+        lock_file(&file, LockKind::Exclusive)?;
+        Self::new_at_end(file)
+    }
+
     /// Create a new file at the path. This will replace any existing file at the path.
     pub fn create(path: &Path) -> Result<Self, TdmsError> {
         let file = File::options()
@@ -51,11 +135,94 @@ impl TdmsFile<File> {
             .open(path)?;
         Self::new(file)
     }
+
+    /// Create a new file at the path holding an exclusive advisory lock.
+    ///
+    /// The existing file is only replaced once the lock is acquired, so a file
+    /// in use by another locked writer is left intact.
+    ///
+    /// See [`Self::open_append_locked`] for details of the locking.
+    pub fn create_locked(path: &Path) -> Result<Self, TdmsError> {
+        let file = File::options()
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .read(true)
+            .open(path)?;
+        lock_file(&file, LockKind::Exclusive)?;
+        file.set_len(0)?;
+        Self::new(file)
+    }
+
+    fn append_options() -> std::fs::OpenOptions {
+        let mut options = File::options();
+        options.read(true).write(true).create(true).truncate(false);
+        options
+    }
+
+    fn new_at_end(file: File) -> Result<Self, TdmsError> {
+        let mut tdms_file = Self::new(file)?;
+        tdms_file.file.seek(SeekFrom::End(0))?;
+        Ok(tdms_file)
+    }
+}
+
+impl TdmsFile<ReadOnly<File>> {
+    /// Open the file from the path for reading only. This will load and index the metadata
+    /// ready for access.
+    ///
+    /// This works on read-only files and filesystems. The returned file
+    /// has no writer methods so it cannot be modified.
+    ///
+    /// ```rust,compile_fail
+    /// use tedium::TdmsFile;
+    ///
+    /// let mut file = TdmsFile::open_read_only("data.tdms".as_ref()).unwrap();
+    /// let writer = file.writer();
+    /// ```
+    pub fn open_read_only(path: &Path) -> Result<Self, TdmsError> {
+        let file = File::options().read(true).open(path)?;
+        Self::new(ReadOnly::new(file))
+    }
+
+    /// Open the file for reading only holding a shared advisory lock.
+    ///
+    /// Any number of readers can hold the shared lock at once but it can't be taken
+    /// while a writer holds an exclusive lock. In that case this returns [`TdmsError::FileLocked`].
+    pub fn open_read_only_locked(path: &Path) -> Result<Self, TdmsError> {
+        let file = File::options().read(true).open(path)?;
+        lock_file(&file, LockKind::Shared)?;
+        Self::new(ReadOnly::new(file))
+    }
+}
+
+#[derive(Clone, Copy)]
+enum LockKind {
+    Shared,
+    Exclusive,
+}
+
+/// Take an advisory lock on the file without blocking.
+///
+/// The lock is released when the file is closed.
+fn lock_file(file: &File, kind: LockKind) -> Result<(), TdmsError> {
+    let result = match kind {
+        LockKind::Shared => file.try_lock_shared(),
+        LockKind::Exclusive => file.try_lock(),
+    };
+    match result {
+        Ok(()) => Ok(()),
+        Err(std::fs::TryLockError::WouldBlock) => Err(TdmsError::FileLocked),
+        Err(std::fs::TryLockError::Error(e)) => Err(e.into()),
+    }
 }
 
 fn build_index(file: &mut (impl Read + Seek)) -> Result<Index, TdmsError> {
-    let mut index = Index::new();
+    index_segments(file, Index::new())
+}
 
+/// Add all of the segments in the file to the given empty index.
+fn index_segments(file: &mut (impl Read + Seek), mut index: Index) -> Result<Index, TdmsError> {
     //Make sure we are at the beginning.
     file.seek(SeekFrom::Start(0))?;
 
@@ -85,7 +252,45 @@ impl<F: Read + Seek> TdmsFile<F> {
     /// ```
     pub fn new(mut file: F) -> Result<Self, TdmsError> {
         let index = build_index(&mut file)?;
-        Ok(Self { index, file })
+        Ok(Self {
+            index,
+            file,
+            cache: None,
+        })
+    }
+
+    /// Create a new file from the given stream, recording every value written to each property.
+    ///
+    /// Normally only the latest value of a property is kept. This also keeps the earlier
+    /// values so they can be read with [`Self::read_property_history`], at the cost of
+    /// holding them all in memory. Properties added through a writer are recorded too.
+    ///
+    /// # Example
+    /// ```rust
+    /// use tedium::{TdmsFile, PropertyPath, PropertyValue};
+    ///
+    /// let mut file = TdmsFile::new_with_property_history(std::io::Cursor::new(vec![])).unwrap();
+    /// let path = PropertyPath::group("group");
+    /// let mut writer = file.writer().unwrap();
+    /// for status in ["idle", "running", "done"] {
+    ///     writer
+    ///         .write_properties(&path, &[("status", PropertyValue::String(status.to_string()))])
+    ///         .unwrap();
+    /// }
+    /// drop(writer);
+    ///
+    /// let history = file.read_property_history(&path, "status").unwrap();
+    /// assert_eq!(history.len(), 3);
+    /// assert_eq!(history[1].segment, 1);
+    /// assert_eq!(history[1].value, PropertyValue::String("running".to_string()));
+    /// ```
+    pub fn new_with_property_history(mut file: F) -> Result<Self, TdmsError> {
+        let index = index_segments(&mut file, Index::with_property_history())?;
+        Ok(Self {
+            index,
+            file,
+            cache: None,
+        })
     }
 
     /// Read the property by name from the full object path.
@@ -119,6 +324,18 @@ impl<F: Read + Seek> TdmsFile<F> {
         self.index.get_object_properties(object_path)
     }
 
+    /// Read every value written to the property in file order with the segment it was written in.
+    ///
+    /// Returns `None` if the property has never been written or the file wasn't opened with
+    /// [`Self::new_with_property_history`].
+    pub fn read_property_history(
+        &self,
+        object_path: &PropertyPath,
+        property: &str,
+    ) -> Option<&[PropertyChange]> {
+        self.index.get_property_history(object_path, property)
+    }
+
     /// Read all groups in the file.
     ///
     /// Returns an iterator to the paths for each group.
@@ -150,6 +367,198 @@ impl<F: Read + Seek> TdmsFile<F> {
         let paths = self.index.paths_starting_with(group.path());
         paths.filter_map(|path| ChannelPath::try_from(path).ok())
     }
+
+    /// Find the groups whose properties match all of the predicates.
+    ///
+    /// Groups which are only implied by the path of their channels have no
+    /// properties so are only returned if there are no predicates.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use tedium::{TdmsFile, PropertyPath, PropertyPredicate, PropertyValue};
+    ///
+    /// let mut file = TdmsFile::new(std::io::Cursor::new(vec![])).unwrap();
+    /// let mut writer = file.writer().unwrap();
+    /// writer
+    ///     .write_properties(
+    ///         &PropertyPath::group("run1"),
+    ///         &[("DUT_Serial", PropertyValue::String("SN-1234".to_string()))],
+    ///     )
+    ///     .unwrap();
+    /// drop(writer);
+    ///
+    /// let groups = file.find_groups(&[PropertyPredicate::glob("DUT_Serial", "SN-*")]);
+    /// assert_eq!(groups, vec![PropertyPath::group("run1")]);
+    /// ```
+    pub fn find_groups(&self, predicates: &[PropertyPredicate]) -> Vec<PropertyPath> {
+        self.index
+            .objects_matching(predicates)
+            .filter(|path| path.group_name().is_some() && path.channel_name().is_none())
+            .collect()
+    }
+
+    /// Find the channels whose properties match all of the predicates.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use tedium::{TdmsFile, Comparison, PropertyPath, PropertyPredicate, PropertyValue};
+    ///
+    /// let mut file = TdmsFile::new(std::io::Cursor::new(vec![])).unwrap();
+    /// let mut writer = file.writer().unwrap();
+    /// writer
+    ///     .write_properties(
+    ///         &PropertyPath::channel("group", "voltage"),
+    ///         &[
+    ///             ("unit_string", PropertyValue::String("V".to_string())),
+    ///             ("NI_ChannelLength", PropertyValue::U64(2_000_000)),
+    ///         ],
+    ///     )
+    ///     .unwrap();
+    /// drop(writer);
+    ///
+    /// let channels = file.find_channels(&[
+    ///     PropertyPredicate::equals("unit_string", PropertyValue::String("V".to_string())),
+    ///     PropertyPredicate::compare("NI_ChannelLength", Comparison::GreaterThan, 1e6),
+    /// ]);
+    /// assert_eq!(channels, vec![PropertyPath::channel("group", "voltage")]);
+    /// ```
+    pub fn find_channels(&self, predicates: &[PropertyPredicate]) -> Vec<PropertyPath> {
+        self.index
+            .objects_matching(predicates)
+            .filter(|path| path.channel_name().is_some())
+            .collect()
+    }
+
+    /// Get a description of the channel including its data type, length and properties.
+    ///
+    /// Returns `None` if the channel doesn't exist.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use tedium::{TdmsFile, ChannelPath, DataLayout, DataType};
+    ///
+    /// let mut file = TdmsFile::new(std::io::Cursor::new(vec![])).unwrap();
+    /// let channel = ChannelPath::new("group", "channel");
+    /// let mut writer = file.writer().unwrap();
+    /// writer.write_channels(&[&channel], &[1.0f64, 2.0, 3.0], DataLayout::Contigious).unwrap();
+    /// drop(writer);
+    ///
+    /// let info = file.channel_info(&channel).unwrap();
+    /// assert_eq!(info.data_type, Some(DataType::DoubleFloat));
+    /// assert_eq!(info.length, 3);
+    /// ```
+    pub fn channel_info(&self, channel: &ChannelPath) -> Option<ChannelInfo> {
+        self.index.channel_info(channel)
+    }
+
+    /// Get the segments in the file in the order they were written.
+    ///
+    /// This exposes the segment structure for debugging how a file was written.
+    /// Reading data doesn't require it, so the index only keeps the position of each
+    /// segment and the headers are read again from the file here.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use tedium::{TdmsFile, ChannelPath, DataLayout};
+    ///
+    /// let mut file = TdmsFile::new(std::io::Cursor::new(vec![])).unwrap();
+    /// let mut writer = file.writer().unwrap();
+    /// writer
+    ///     .write_channels(&[ChannelPath::new("group", "channel")], &[1.0, 2.0], DataLayout::Contigious)
+    ///     .unwrap();
+    /// drop(writer);
+    ///
+    /// for segment in file.segments().unwrap() {
+    ///     println!(
+    ///         "segment at {} with {} objects and {} bytes of data",
+    ///         segment.offset,
+    ///         segment.objects.len(),
+    ///         segment.raw_data_size()
+    ///     );
+    /// }
+    /// ```
+    pub fn segments(&mut self) -> Result<Vec<SegmentInfo>, TdmsError> {
+        let mut segments = Vec::with_capacity(self.index.segments().len());
+        for record in self.index.segments() {
+            self.file.seek(SeekFrom::Start(record.offset))?;
+            let segment = Segment::read(&mut self.file)?;
+            segments.push(SegmentInfo::from_segment(
+                &segment,
+                record.offset,
+                record.data_block,
+            ));
+        }
+        Ok(segments)
+    }
+
+    /// Get a summary of each data block in the file.
+    ///
+    /// The data block indexes match [`SegmentInfo::data_block`] and [`DataLocation::data_block`].
+    pub fn data_blocks(&self) -> impl Iterator<Item = DataBlockInfo> + '_ {
+        (0..self.index.data_block_count()).filter_map(|index| self.index.data_block_info(index))
+    }
+
+    /// Get a summary of the data block at the index, or `None` if it doesn't exist.
+    pub fn data_block(&self, index: usize) -> Option<DataBlockInfo> {
+        self.index.data_block_info(index)
+    }
+
+    /// Get the locations of the data for the channel in the file.
+    ///
+    /// Returns `None` if the channel doesn't exist.
+    pub fn channel_data_locations(&self, channel: &ChannelPath) -> Option<&[DataLocation]> {
+        self.index.get_channel_data_positions(channel)
+    }
+
+    /// Cache channel data to speed up repeated reads of the same data blocks.
+    ///
+    /// On a cache miss the whole of the channel's raw data in the block is read so
+    /// later reads of any part of it are decoded from memory. The least recently used data is
+    /// dropped to keep the cache within `max_bytes`. Blocks larger than `max_bytes` and
+    /// string channels are read directly and never cached.
+    ///
+    /// The cache holds the raw bytes rather than decoded values, so a hit saves the
+    /// file read but the values are still converted from the file byte order on every read.
+    ///
+    /// Calling this again replaces the cache, clearing its contents and statistics.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use tedium::{TdmsFile, ChannelPath, DataLayout};
+    ///
+    /// let mut file = TdmsFile::new(std::io::Cursor::new(vec![])).unwrap();
+    /// let channel = ChannelPath::new("group", "channel");
+    /// let mut writer = file.writer().unwrap();
+    /// writer.write_channels(&[&channel], &[1.0, 2.0, 3.0], DataLayout::Contigious).unwrap();
+    /// drop(writer);
+    ///
+    /// file.enable_block_cache(16 * 1024 * 1024);
+    /// let mut output = [0.0f64; 3];
+    /// file.read_channel(&channel, &mut output).unwrap();
+    /// file.read_channel(&channel, &mut output).unwrap();
+    ///
+    /// let stats = file.block_cache_stats().unwrap();
+    /// assert_eq!(stats.misses, 1);
+    /// assert_eq!(stats.hits, 1);
+    /// ```
+    pub fn enable_block_cache(&mut self, max_bytes: usize) {
+        self.cache = Some(BlockCache::new(max_bytes));
+    }
+
+    /// Remove the block cache, freeing the cached data.
+    pub fn disable_block_cache(&mut self) {
+        self.cache = None;
+    }
+
+    /// Get the statistics for the block cache, or `None` if it isn't enabled.
+    pub fn block_cache_stats(&self) -> Option<BlockCacheStats> {
+        self.cache.as_ref().map(BlockCache::stats)
+    }
 }
 
 impl<F: Write + Read + Seek> TdmsFile<F> {
@@ -186,6 +595,88 @@ impl<F: Write + Read + Seek> TdmsFile<F> {
             LittleEndianWriter::from_writer(&mut self.file),
         ))
     }
+
+    /// Convert the file into an owned writer.
+    ///
+    /// Unlike [`Self::writer`] the returned writer owns the file and index so it
+    /// can be moved to another thread. Use [`TdmsWriterHandle::into_file`] to get
+    /// back a [`TdmsFile`] for reading.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use tedium::{TdmsFile, ChannelPath, DataLayout};
+    ///
+    /// let fake_file = std::io::Cursor::new(vec![]);
+    /// let file = TdmsFile::new(fake_file).unwrap();
+    /// let mut writer = file.into_writer().unwrap();
+    ///
+    /// writer.write_channels(
+    ///    &[ChannelPath::new("group", "channel")],
+    ///    &[1.0, 2.0, 3.0],
+    ///    DataLayout::Contigious,
+    /// ).unwrap();
+    ///
+    /// let mut file = writer.into_file().unwrap();
+    /// file.read_channel(&ChannelPath::new("group", "channel"), &mut [0.0f64; 3]).unwrap();
+    /// ```
+    pub fn into_writer(mut self) -> Result<TdmsWriterHandle<F>, TdmsError> {
+        //make sure we are at the end.
+        self.file.seek(SeekFrom::End(0))?;
+        Ok(TdmsWriterHandle::new(
+            self.index,
+            LittleEndianWriter::from_writer(self.file),
+        ))
+    }
+
+    /// Get a writer which writes big endian segments.
+    ///
+    /// This is the same as [`Self::writer`] except the segments are written with
+    /// big endian byte order, for readers that expect it. The byte order is set per
+    /// segment so this can be used on files which contain little endian data.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use tedium::{TdmsFile, ChannelPath, DataLayout};
+    ///
+    /// let mut fake_file = std::io::Cursor::new(vec![]);
+    /// let mut file = TdmsFile::new(fake_file).unwrap();
+    /// let mut writer = file.big_endian_writer().unwrap();
+    ///
+    /// writer.write_channels(
+    ///    &[ChannelPath::new("group", "channel")],
+    ///    &[1.0, 2.0, 3.0],
+    ///    DataLayout::Contigious,
+    /// ).unwrap();
+    /// drop(writer);
+    ///
+    /// file.read_channel(&ChannelPath::new("group", "channel"), &mut [0.0f64; 3]).unwrap();
+    /// ```
+    pub fn big_endian_writer(
+        &mut self,
+    ) -> Result<TdmsFileWriter<'_, F, BigEndianWriter<&mut F>>, TdmsError> {
+        //make sure we are at the end.
+        self.file.seek(SeekFrom::End(0))?;
+        Ok(TdmsFileWriter::new(
+            &mut self.index,
+            BigEndianWriter::from_writer(&mut self.file),
+        ))
+    }
+
+    /// Convert the file into an owned writer which writes big endian segments.
+    ///
+    /// See [`Self::into_writer`] and [`Self::big_endian_writer`].
+    pub fn into_big_endian_writer(
+        mut self,
+    ) -> Result<TdmsWriterHandle<F, BigEndianWriter<F>>, TdmsError> {
+        //make sure we are at the end.
+        self.file.seek(SeekFrom::End(0))?;
+        Ok(TdmsWriterHandle::new(
+            self.index,
+            BigEndianWriter::from_writer(self.file),
+        ))
+    }
 }
 
 #[cfg(test)]
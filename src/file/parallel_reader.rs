@@ -0,0 +1,285 @@
+//! Parallel execution of read plans using rayon.
+//!
+//! The file is still read sequentially as we only have a single cursor but the
+//! raw bytes for each data block are then decoded on the rayon thread pool.
+//! Large blocks are split into pieces so no single read is larger than [`MAX_SPAN_BYTES`].
+//! Each block decodes into its own part of the output slices so no synchronisation
+//! is needed between them.
+
+use std::io::{Read, Seek};
+
+use rayon::prelude::*;
+
+use super::block_pieces::{BlockPiece, split_block};
+use super::channel_reader::{
+    BlockBuffer, BlockRead, ChannelProgress, MAX_SPAN_BYTES, plan_channels_read,
+};
+use crate::error::TdmsError;
+use crate::index::Index;
+use crate::io::data_types::TdmsStorageType;
+use crate::raw_data::{BlockReadChannelConfig, DataBlock};
+use crate::{ChannelPath, TdmsFile};
+
+/// The amount of raw data to load before decoding it in parallel.
+///
+/// This limits the extra memory used to hold the raw bytes.
+const BATCH_BYTES: u64 = 64 * 1024 * 1024;
+
+impl<F: Read + Seek> TdmsFile<F> {
+    /// Read multiple channels from the tdms file, decoding data blocks in parallel.
+    ///
+    /// This behaves like [`Self::read_channels`] but decodes each data block on the rayon
+    /// thread pool. It is most effective for large reads covering many data blocks.
+    ///
+    /// Data blocks are loaded into memory before decoding, so reading a few channels
+    /// from blocks containing many channels may read more from the disk than [`Self::read_channels`].
+    pub fn par_read_channels<D: TdmsStorageType + Send>(
+        &mut self,
+        channels: &[impl AsRef<ChannelPath>],
+        output: &mut [&mut [D]],
+    ) -> Result<(), TdmsError> {
+        self.par_read_channels_from(channels, 0, output)
+    }
+
+    /// Read multiple channels from the tdms file starting at a specific sample position,
+    /// decoding data blocks in parallel.
+    ///
+    /// See [`Self::read_channels_from`] and [`Self::par_read_channels`].
+    pub fn par_read_channels_from<D: TdmsStorageType + Send>(
+        &mut self,
+        channels: &[impl AsRef<ChannelPath>],
+        start: u64,
+        output: &mut [&mut [D]],
+    ) -> Result<(), TdmsError> {
+        let plan = plan_channels_read(&self.index, channels, start)?;
+        execute_read_plan_parallel(&self.index, &mut self.file, plan, output)
+    }
+}
+
+/// A block to decode with the parts of the output it writes to.
+struct BlockTask<'o, D: TdmsStorageType> {
+    block: DataBlock,
+    buffer: BlockBuffer,
+    channels: Vec<BlockReadChannelConfig<'o, D>>,
+}
+
+impl<D: TdmsStorageType> BlockTask<'_, D> {
+    fn decode(mut self) -> Result<(), TdmsError> {
+        // Reborrow the outputs as the block reader needs them for the same lifetime as the slice.
+        let mut channels: Vec<BlockReadChannelConfig<D>> = self
+            .channels
+            .iter_mut()
+            .map(|channel| BlockReadChannelConfig {
+                channel_index: channel.channel_index,
+                samples_to_skip: channel.samples_to_skip,
+                output: &mut *channel.output,
+            })
+            .collect();
+        self.block
+            .read_with_per_channel_skip(&mut self.buffer, &mut channels)?;
+        Ok(())
+    }
+}
+
+/// Execute the read plan, loading the raw data sequentially and decoding the blocks in parallel.
+fn execute_read_plan_parallel<D: TdmsStorageType + Send>(
+    index: &Index,
+    file: &mut (impl Read + Seek),
+    plan: Vec<BlockRead>,
+    output: &mut [&mut [D]],
+) -> Result<(), TdmsError> {
+    // The unread part of each output. We split off the part for each block as we go.
+    let mut remaining: Vec<&mut [D]> = output.iter_mut().map(|output| &mut output[..]).collect();
+
+    let mut batch: Vec<BlockTask<D>> = Vec::new();
+    let mut batch_bytes = 0;
+
+    for location in plan {
+        if remaining.iter().all(|output| output.is_empty()) {
+            break;
+        }
+
+        let block = index.get_data_block(location.data_block).ok_or_else(|| {
+            TdmsError::DataBlockNotFound(ChannelPath::new("MIXED", "MIXED"), location.data_block)
+        })?;
+        let pieces = if block.length.get() > MAX_SPAN_BYTES {
+            let progress: Vec<ChannelProgress> = remaining
+                .iter()
+                .map(|output| ChannelProgress::new(output.len()))
+                .collect();
+            split_block(block, &location, &progress, MAX_SPAN_BYTES)?
+        } else {
+            vec![BlockPiece {
+                block: block.clone(),
+                location,
+            }]
+        };
+
+        for piece in pieces {
+            let Some(task) = load_task(file, piece, &mut remaining)? else {
+                continue;
+            };
+            batch_bytes += task.block.length.get();
+            batch.push(task);
+
+            if batch_bytes >= BATCH_BYTES {
+                decode_batch(&mut batch)?;
+                batch_bytes = 0;
+            }
+        }
+    }
+
+    decode_batch(&mut batch)
+}
+
+/// Split off the parts of the outputs that the piece of a block fills and load its raw data.
+///
+/// Returns `None` without reading the file if the piece has nothing for the outputs.
+fn load_task<'o, D: TdmsStorageType>(
+    file: &mut (impl Read + Seek),
+    piece: BlockPiece,
+    remaining: &mut [&'o mut [D]],
+) -> Result<Option<BlockTask<'o, D>>, TdmsError> {
+    let BlockPiece { block, location } = piece;
+    let chunks = block.number_of_chunks()? as u64;
+
+    let mut channels = Vec::new();
+    for (plan, output) in location.channel_indexes.iter().zip(remaining.iter_mut()) {
+        let Some(plan) = plan else {
+            continue;
+        };
+        let meta = block.channels.get(plan.index).ok_or_else(|| {
+            TdmsError::DataBlockNotFound(ChannelPath::new("MIXED", "MIXED"), location.data_block)
+        })?;
+        let available = meta
+            .number_of_values
+            .checked_mul(chunks)
+            .ok_or(TdmsError::ChunkSizeOverflow)?
+            .saturating_sub(plan.samples_to_skip);
+        let count = usize::try_from(available)
+            .map_or(output.len(), |available| output.len().min(available));
+        if count == 0 {
+            continue;
+        }
+        let (block_output, rest) = std::mem::take(output).split_at_mut(count);
+        *output = rest;
+        channels.push(BlockReadChannelConfig {
+            channel_index: plan.index,
+            samples_to_skip: plan.samples_to_skip,
+            output: block_output,
+        });
+    }
+
+    if channels.is_empty() {
+        return Ok(None);
+    }
+    let buffer = BlockBuffer::load(file, block.start, block.length.get())?;
+    Ok(Some(BlockTask {
+        block,
+        buffer,
+        channels,
+    }))
+}
+
+fn decode_batch<D: TdmsStorageType + Send>(batch: &mut Vec<BlockTask<D>>) -> Result<(), TdmsError> {
+    batch.par_drain(..).try_for_each(BlockTask::decode)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::DataLayout;
+    use std::io::Cursor;
+
+    fn build_file(blocks: usize, layout: DataLayout) -> TdmsFile<Cursor<Vec<u8>>> {
+        build_file_with_blocks(blocks, 300, layout)
+    }
+
+    fn build_file_with_blocks(
+        blocks: usize,
+        block_values: usize,
+        layout: DataLayout,
+    ) -> TdmsFile<Cursor<Vec<u8>>> {
+        let mut file = TdmsFile::new(Cursor::new(Vec::new())).unwrap();
+        let mut writer = file.writer().unwrap();
+        let channels = [
+            ChannelPath::new("group", "ch1"),
+            ChannelPath::new("group", "ch2"),
+            ChannelPath::new("group", "ch3"),
+        ];
+        for block in 0..blocks {
+            let values: Vec<f64> = (0..block_values)
+                .map(|i| (block * 1000 + i) as f64)
+                .collect();
+            writer.write_channels(&channels, &values, layout).unwrap();
+        }
+        drop(writer);
+        file
+    }
+
+    fn compare_with_sequential(layout: DataLayout, start: u64, length: usize) {
+        let mut file = build_file(20, layout);
+        compare_file_with_sequential(&mut file, start, length);
+    }
+
+    fn compare_file_with_sequential(
+        file: &mut TdmsFile<Cursor<Vec<u8>>>,
+        start: u64,
+        length: usize,
+    ) {
+        let channels = [
+            ChannelPath::new("group", "ch3"),
+            ChannelPath::new("group", "ch1"),
+        ];
+
+        let mut expected1 = vec![0.0f64; length];
+        let mut expected2 = vec![0.0f64; length];
+        file.read_channels_from(&channels, start, &mut [&mut expected1, &mut expected2])
+            .unwrap();
+
+        let mut parallel1 = vec![0.0f64; length];
+        let mut parallel2 = vec![0.0f64; length];
+        file.par_read_channels_from(&channels, start, &mut [&mut parallel1, &mut parallel2])
+            .unwrap();
+
+        assert_eq!(parallel1, expected1);
+        assert_eq!(parallel2, expected2);
+    }
+
+    #[test]
+    fn parallel_matches_sequential_contiguous() {
+        compare_with_sequential(DataLayout::Contigious, 0, 2000);
+    }
+
+    #[test]
+    fn parallel_matches_sequential_interleaved() {
+        compare_with_sequential(DataLayout::Interleaved, 0, 2000);
+    }
+
+    #[test]
+    fn parallel_matches_sequential_with_offset() {
+        compare_with_sequential(DataLayout::Contigious, 250, 1000);
+        compare_with_sequential(DataLayout::Interleaved, 250, 1000);
+    }
+
+    #[test]
+    fn parallel_splits_large_blocks() {
+        for layout in [DataLayout::Contigious, DataLayout::Interleaved] {
+            // Each block is 3.6 MB so it is loaded in pieces.
+            let mut file = build_file_with_blocks(2, 450_000, layout);
+            compare_file_with_sequential(&mut file, 0, 300_000);
+            compare_file_with_sequential(&mut file, 100_000, 150_000);
+        }
+    }
+
+    #[test]
+    fn parallel_output_longer_than_channel() {
+        let mut file = build_file(2, DataLayout::Contigious);
+        let mut output = vec![-1.0f64; 300];
+        file.par_read_channels(&[ChannelPath::new("group", "ch2")], &mut [&mut output])
+            .unwrap();
+        assert_eq!(output[0], 100.0);
+        assert_eq!(output[199], 1199.0);
+        assert_eq!(output[200], -1.0);
+    }
+}
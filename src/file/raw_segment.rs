@@ -0,0 +1,129 @@
+//! Read the raw bytes of data blocks without decoding the samples.
+//!
+//! This is useful for passing data on to other software which decodes it itself,
+//! using the block description to interpret the bytes.
+
+use std::io::{Read, Seek, SeekFrom};
+
+use super::TdmsFile;
+use crate::error::TdmsError;
+use crate::index::DataBlockInfo;
+
+/// The undecoded raw data from a data block.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RawDataBlock {
+    /// The description of the data block including the channel formats, layout and byte order.
+    pub info: DataBlockInfo,
+    /// The raw data exactly as it is stored in the file.
+    ///
+    /// This can be shorter than [`DataBlockInfo::length`] if the file was truncated
+    /// part way through the final segment.
+    pub data: Vec<u8>,
+}
+
+impl<F: Read + Seek> TdmsFile<F> {
+    /// Read the raw data of the segment at the index in [`Self::segments`].
+    ///
+    /// Returns `None` if the segment doesn't exist or contains no raw data.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use tedium::{TdmsFile, ChannelPath, DataLayout, Endianess};
+    ///
+    /// let mut file = TdmsFile::new(std::io::Cursor::new(vec![])).unwrap();
+    /// let mut writer = file.writer().unwrap();
+    /// writer
+    ///     .write_channels(&[ChannelPath::new("group", "channel")], &[1.0f64, 2.0], DataLayout::Contigious)
+    ///     .unwrap();
+    /// drop(writer);
+    ///
+    /// let raw = file.read_raw_segment(0).unwrap().unwrap();
+    /// assert_eq!(raw.info.byte_order, Endianess::Little);
+    /// assert_eq!(raw.data, [1.0f64.to_le_bytes(), 2.0f64.to_le_bytes()].concat());
+    /// ```
+    pub fn read_raw_segment(&mut self, segment: usize) -> Result<Option<RawDataBlock>, TdmsError> {
+        match self
+            .index
+            .segments()
+            .get(segment)
+            .and_then(|segment| segment.data_block)
+        {
+            Some(block) => self.read_raw_data_block(block),
+            None => Ok(None),
+        }
+    }
+
+    /// Read the raw data of the data block at the index in [`Self::data_blocks`].
+    ///
+    /// Returns `None` if the data block doesn't exist.
+    pub fn read_raw_data_block(&mut self, block: usize) -> Result<Option<RawDataBlock>, TdmsError> {
+        let Some(info) = self.index.data_block_info(block) else {
+            return Ok(None);
+        };
+
+        self.file.seek(SeekFrom::Start(info.start))?;
+        let mut data = Vec::new();
+        data.try_reserve_exact(info.length as usize)
+            .map_err(|_| TdmsError::VecAllocationFailed)?;
+        (&mut self.file).take(info.length).read_to_end(&mut data)?;
+
+        Ok(Some(RawDataBlock { info, data }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use super::*;
+    use crate::{ChannelPath, DataLayout, PropertyPath, PropertyValue};
+
+    fn channels() -> [ChannelPath; 2] {
+        [
+            ChannelPath::new("group", "ch1"),
+            ChannelPath::new("group", "ch2"),
+        ]
+    }
+
+    #[test]
+    fn reads_interleaved_bytes_unchanged() {
+        let mut file = TdmsFile::new(Cursor::new(Vec::new())).unwrap();
+        let mut writer = file.big_endian_writer().unwrap();
+        writer
+            .write_channels(&channels(), &[1u16, 2, 3, 4], DataLayout::Interleaved)
+            .unwrap();
+        drop(writer);
+
+        let raw = file.read_raw_segment(0).unwrap().unwrap();
+        assert_eq!(raw.data, [0, 1, 0, 2, 0, 3, 0, 4]);
+        assert_eq!(raw.info.layout, DataLayout::Interleaved);
+        assert_eq!(raw.info.channels.len(), 2);
+        assert_eq!(raw.info.channels[1].format.number_of_values, 2);
+    }
+
+    #[test]
+    fn segments_without_data_return_none() {
+        let mut file = TdmsFile::new(Cursor::new(Vec::new())).unwrap();
+        let mut writer = file.writer().unwrap();
+        writer
+            .write_properties(
+                &PropertyPath::file(),
+                &[("name", PropertyValue::String("test".to_string()))],
+            )
+            .unwrap();
+        writer
+            .write_channels(&channels(), &[1u8, 2], DataLayout::Contigious)
+            .unwrap();
+        drop(writer);
+
+        assert_eq!(file.read_raw_segment(0).unwrap(), None);
+        assert_eq!(file.read_raw_segment(2).unwrap(), None);
+        assert_eq!(file.read_raw_data_block(1).unwrap(), None);
+
+        let by_segment = file.read_raw_segment(1).unwrap().unwrap();
+        let by_block = file.read_raw_data_block(0).unwrap().unwrap();
+        assert_eq!(by_segment, by_block);
+        assert_eq!(by_block.data, [1, 2]);
+    }
+}
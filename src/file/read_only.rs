@@ -0,0 +1,37 @@
+//! A wrapper to restrict a stream to read access.
+
+use std::io::{Read, Seek, SeekFrom};
+
+/// Wraps a stream so that only [`Read`] and [`Seek`] are available.
+///
+/// A [`crate::TdmsFile`] over this type has no writer methods so the
+/// file cannot be modified through it.
+///
+/// Normally this is created by [`crate::TdmsFile::open_read_only`] but you can
+/// wrap any stream to prevent writes.
+#[derive(Debug)]
+pub struct ReadOnly<F>(F);
+
+impl<F: Read + Seek> ReadOnly<F> {
+    /// Wrap the stream.
+    pub fn new(inner: F) -> Self {
+        Self(inner)
+    }
+
+    /// Get the underlying stream back.
+    pub fn into_inner(self) -> F {
+        self.0
+    }
+}
+
+impl<F: Read> Read for ReadOnly<F> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        self.0.read(buf)
+    }
+}
+
+impl<F: Seek> Seek for ReadOnly<F> {
+    fn seek(&mut self, pos: SeekFrom) -> std::io::Result<u64> {
+        self.0.seek(pos)
+    }
+}
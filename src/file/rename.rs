@@ -0,0 +1,363 @@
+//! Rename groups and channels.
+//!
+//! Object paths are stored in the metadata of every segment which lists the object.
+//! If the new path is the same length as the old one we can overwrite each copy in
+//! place. Otherwise the file has to be rewritten with [`TdmsFile::copy_filtered`].
+
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::{BufWriter, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+
+use super::{CopyFilter, TdmsFile, index_segments};
+use crate::PropertyPath;
+use crate::error::TdmsError;
+use crate::index::Index;
+use crate::meta_data::{LEAD_IN_BYTES, Segment, TdmsMetaData};
+use crate::paths::renamed_path;
+
+/// Apply the renames in order to the path.
+///
+/// Returns the new path and the index of the last rename which changed it.
+pub(super) fn apply_renames(
+    path: &str,
+    renames: &[(PropertyPath, PropertyPath)],
+) -> (String, Option<usize>) {
+    let mut output = path.to_string();
+    let mut applied = None;
+    for (index, (from, to)) in renames.iter().enumerate() {
+        if let Some(renamed) = renamed_path(&output, from, to) {
+            output = renamed.path().to_string();
+            applied = Some(index);
+        }
+    }
+    (output, applied)
+}
+
+/// Check the renames are valid for the objects in the index.
+pub(super) fn check_renames(
+    index: &Index,
+    renames: &[(PropertyPath, PropertyPath)],
+) -> Result<(), TdmsError> {
+    let invalid = |rename: &(PropertyPath, PropertyPath), reason| {
+        TdmsError::InvalidRename(rename.0.to_string(), rename.1.to_string(), reason)
+    };
+
+    for rename in renames {
+        let (from, to) = rename;
+        if from.group_name().is_none()
+            || to.group_name().is_none()
+            || from.channel_name().is_some() != to.channel_name().is_some()
+        {
+            return Err(invalid(
+                rename,
+                "they must both be groups or both be channels",
+            ));
+        }
+    }
+
+    // Renaming onto an existing object would merge them so make sure every path is unique.
+    let mut output_paths: BTreeMap<String, Option<usize>> = BTreeMap::new();
+    for path in index.all_paths() {
+        let (output, applied) = apply_renames(path, renames);
+        if let Some(existing) = output_paths.insert(output, applied)
+            && let Some(rename) = applied.or(existing)
+        {
+            return Err(invalid(&renames[rename], "the new path is already in use"));
+        }
+    }
+    Ok(())
+}
+
+impl<F: Read + Write + Seek> TdmsFile<F> {
+    /// Rename a group or channel by overwriting the paths in the existing metadata.
+    ///
+    /// Renaming a group also moves its channels. This is only possible when the new
+    /// path has the same length in bytes as the old one. Returns `false` without changing
+    /// the file if it isn't possible, in which case [`Self::copy_filtered`] with
+    /// [`CopyFilter::rename`] can write a renamed copy instead.
+    ///
+    /// Returns [`TdmsError::MissingObject`] if nothing in the file has the `from` path.
+    ///
+    /// The paths are overwritten one at a time so this is not atomic. If writing fails
+    /// or the process stops part way through, some segments can be left with the old
+    /// path. Write a renamed copy with [`Self::copy_filtered`] and move it over the
+    /// original if the file must survive a crash.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use tedium::{ChannelPath, DataLayout, PropertyPath, TdmsFile};
+    ///
+    /// let mut file = TdmsFile::new(std::io::Cursor::new(vec![])).unwrap();
+    /// let mut writer = file.writer().unwrap();
+    /// writer
+    ///     .write_channels(&[ChannelPath::new("rig1", "temp")], &[1.0, 2.0], DataLayout::Contigious)
+    ///     .unwrap();
+    /// drop(writer);
+    ///
+    /// let renamed = file
+    ///     .rename_in_place(&PropertyPath::group("rig1"), &PropertyPath::group("rig2"))
+    ///     .unwrap();
+    /// assert!(renamed);
+    /// assert_eq!(file.channel_length(&ChannelPath::new("rig2", "temp")), Some(2));
+    /// ```
+    pub fn rename_in_place(
+        &mut self,
+        from: &PropertyPath,
+        to: &PropertyPath,
+    ) -> Result<bool, TdmsError> {
+        let renames = [(from.clone(), to.clone())];
+        check_renames(&self.index, &renames)?;
+        if !self
+            .index
+            .all_paths()
+            .any(|path| renamed_path(path, from, to).is_some())
+        {
+            return Err(TdmsError::MissingObject(from.path().to_string()));
+        }
+
+        // Find every copy of the paths before changing anything so we can give up
+        // without writing if any of them can't be renamed in place.
+        let mut edits = Vec::new();
+        for segment in self.index.segments() {
+            if !segment.toc.contains_meta_data {
+                continue;
+            }
+            self.file.seek(SeekFrom::Start(segment.offset))?;
+            let Some(meta_data) = Segment::read(&mut self.file)?.meta_data else {
+                continue;
+            };
+
+            // Skip the lead in and the object count.
+            let mut position = segment.offset + LEAD_IN_BYTES + 4;
+            for object in meta_data.objects {
+                if let Some(new_path) = renamed_path(&object.path, from, to) {
+                    if new_path.path().len() != object.path.len() {
+                        return Ok(false);
+                    }
+                    // The path follows its length.
+                    edits.push((position + 4, object.path.clone(), new_path));
+                }
+                position += object.size() as u64;
+            }
+        }
+
+        // Check the old paths are where we expect in case the metadata uses a layout
+        // we don't reproduce exactly.
+        for (position, old_path, _) in &edits {
+            let mut found = vec![0u8; old_path.len()];
+            self.file.seek(SeekFrom::Start(*position))?;
+            self.file.read_exact(&mut found)?;
+            if found != old_path.as_bytes() {
+                return Ok(false);
+            }
+        }
+
+        for (position, _, new_path) in &edits {
+            self.file.seek(SeekFrom::Start(*position))?;
+            self.file.write_all(new_path.path().as_bytes())?;
+        }
+        self.file.flush()?;
+
+        let index = if self.index.has_property_history() {
+            Index::with_property_history()
+        } else {
+            Index::new()
+        };
+        self.index = index_segments(&mut self.file, index)?;
+        Ok(true)
+    }
+}
+
+impl TdmsFile<File> {
+    /// Rename a group or channel in the file at the path.
+    ///
+    /// This renames the file in place if the new path is the same length as the old one,
+    /// which is not atomic (see [`TdmsFile::rename_in_place`]). Otherwise a renamed copy
+    /// is written next to the file and then moved over it, so there must be room for a
+    /// second copy of the file. The copy is written to the path with `.renaming`
+    /// appended, and this fails if a file already exists there.
+    ///
+    /// See [`TdmsFile::rename_in_place`] for the rules of renaming.
+    pub fn rename_object(
+        path: &Path,
+        from: &PropertyPath,
+        to: &PropertyPath,
+    ) -> Result<(), TdmsError> {
+        let mut file = Self::load(path)?;
+        if file.rename_in_place(from, to)? {
+            return Ok(());
+        }
+
+        let mut temp_path = path.as_os_str().to_owned();
+        temp_path.push(".renaming");
+        let temp_path = PathBuf::from(temp_path);
+
+        let filter = CopyFilter::new().rename(from, to);
+        // Don't replace a file we didn't create.
+        let output = File::options()
+            .write(true)
+            .create_new(true)
+            .open(&temp_path)?;
+        let copied = (|| {
+            let mut output = BufWriter::new(output);
+            file.copy_filtered(&mut output, &filter)?;
+            let output = output.into_inner().map_err(|e| e.into_error())?;
+            output.sync_all()?;
+            Ok::<_, TdmsError>(())
+        })();
+        if let Err(e) = copied {
+            let _ = std::fs::remove_file(&temp_path);
+            return Err(e);
+        }
+
+        // Close the original before replacing it.
+        drop(file);
+        std::fs::rename(&temp_path, path)?;
+        // The rename is only durable once the directory entry is written.
+        #[cfg(unix)]
+        {
+            let directory = match path.parent() {
+                Some(parent) if !parent.as_os_str().is_empty() => parent,
+                _ => Path::new("."),
+            };
+            File::open(directory)?.sync_all()?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use super::*;
+    use crate::{ChannelPath, DataLayout, PropertyValue};
+
+    fn build_file() -> TdmsFile<Cursor<Vec<u8>>> {
+        let mut file = TdmsFile::new(Cursor::new(Vec::new())).unwrap();
+        let mut writer = file.writer().unwrap();
+        writer
+            .write_properties(
+                &PropertyPath::group("rig1"),
+                &[("serial", PropertyValue::String("A".to_string()))],
+            )
+            .unwrap();
+        for block in 0..3 {
+            let values: Vec<f64> = (0..20).map(|i| (block * 20 + i) as f64).collect();
+            writer
+                .write_channels(
+                    &[
+                        ChannelPath::new("rig1", "temp"),
+                        ChannelPath::new("rig10", "temp"),
+                    ],
+                    &values,
+                    DataLayout::Interleaved,
+                )
+                .unwrap();
+        }
+        drop(writer);
+        file
+    }
+
+    fn read(file: &mut TdmsFile<impl Read + Seek>, channel: &ChannelPath) -> Vec<f64> {
+        let mut output = vec![0.0; file.channel_length(channel).unwrap() as usize];
+        file.read_channel(channel, &mut output).unwrap();
+        output
+    }
+
+    #[test]
+    fn renames_group_in_place() {
+        let mut file = build_file();
+        let expected = read(&mut file, &ChannelPath::new("rig1", "temp"));
+        let length = file.file.get_ref().len();
+
+        assert!(
+            file.rename_in_place(&PropertyPath::group("rig1"), &PropertyPath::group("rigA"))
+                .unwrap()
+        );
+
+        assert_eq!(file.file.get_ref().len(), length);
+        assert_eq!(read(&mut file, &ChannelPath::new("rigA", "temp")), expected);
+        assert_eq!(file.channel_length(&ChannelPath::new("rig1", "temp")), None);
+        assert_eq!(
+            file.read_property(&PropertyPath::group("rigA"), "serial")
+                .unwrap(),
+            Some(&PropertyValue::String("A".to_string()))
+        );
+        // Only the exact group is renamed.
+        assert_eq!(
+            file.channel_length(&ChannelPath::new("rig10", "temp")),
+            Some(30)
+        );
+
+        // The change is in the file, not just the index.
+        let mut reopened = TdmsFile::new(Cursor::new(file.file.get_ref().clone())).unwrap();
+        assert_eq!(
+            read(&mut reopened, &ChannelPath::new("rigA", "temp")),
+            expected
+        );
+    }
+
+    #[test]
+    fn different_length_is_not_renamed_in_place() {
+        let mut file = build_file();
+        let original = file.file.get_ref().clone();
+        let from = PropertyPath::channel("rig1", "temp");
+        let to = PropertyPath::channel("rig1", "temperature");
+
+        assert!(!file.rename_in_place(&from, &to).unwrap());
+        assert_eq!(file.file.get_ref(), &original);
+
+        let mut output = Cursor::new(Vec::new());
+        file.copy_filtered(&mut output, &CopyFilter::new().rename(&from, &to))
+            .unwrap();
+        let mut copy = TdmsFile::new(output).unwrap();
+        assert_eq!(
+            read(&mut copy, &ChannelPath::new("rig1", "temperature")),
+            read(&mut file, &ChannelPath::new("rig1", "temp"))
+        );
+        assert_eq!(copy.channel_length(&ChannelPath::new("rig1", "temp")), None);
+    }
+
+    #[test]
+    fn rename_handles_quotes() {
+        let mut file = build_file();
+        let to = PropertyPath::group("rig'");
+        assert!(
+            file.rename_in_place(&PropertyPath::group("rig1"), &to)
+                .unwrap()
+        );
+        let groups: Vec<_> = file.list_groups().collect();
+        assert_eq!(groups, [r#"rig""#, "rig10"]);
+        assert_eq!(
+            file.channel_length(&ChannelPath::new("rig'", "temp")),
+            Some(30)
+        );
+    }
+
+    #[test]
+    fn invalid_renames_are_rejected() {
+        let mut file = build_file();
+        let original = file.file.get_ref().clone();
+
+        let result = file.rename_in_place(
+            &PropertyPath::group("rig1"),
+            &PropertyPath::channel("rig1", "temp"),
+        );
+        assert!(matches!(result, Err(TdmsError::InvalidRename(..))));
+
+        let result = file.rename_in_place(
+            &PropertyPath::channel("rig1", "temp"),
+            &PropertyPath::channel("rig10", "temp"),
+        );
+        assert!(matches!(result, Err(TdmsError::InvalidRename(..))));
+
+        let result =
+            file.rename_in_place(&PropertyPath::group("rig2"), &PropertyPath::group("rig3"));
+        assert!(matches!(result, Err(TdmsError::MissingObject(_))));
+
+        assert_eq!(file.file.get_ref(), &original);
+    }
+}
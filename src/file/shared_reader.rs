@@ -0,0 +1,212 @@
+//! A reader which can be shared between threads.
+//!
+//! The standard [`TdmsFile`] moves the file cursor to read so all reads need `&mut self`.
+//! Here we use positional reads instead so there is no shared cursor and reads only
+//! need `&self`.
+
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom};
+use std::path::Path;
+
+use super::channel_reader::{execute_read_plan, plan_channels_read};
+use crate::error::TdmsError;
+use crate::index::Index;
+use crate::io::data_types::TdmsStorageType;
+use crate::{ChannelPath, PropertyPath, PropertyValue, TdmsFile};
+
+/// A read only TDMS file which can be read from multiple threads at once.
+///
+/// Wrap it in an [`std::sync::Arc`] to share it. Each read plans from the shared index
+/// and reads the file with positional reads so reads of different channels don't
+/// interfere with each other.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use std::sync::Arc;
+/// use tedium::{ChannelPath, SharedTdmsFile};
+///
+/// let file = Arc::new(SharedTdmsFile::open("data.tdms".as_ref()).unwrap());
+///
+/// let handles: Vec<_> = ["ch1", "ch2"]
+///     .into_iter()
+///     .map(|channel| {
+///         let file = file.clone();
+///         std::thread::spawn(move || {
+///             let mut output = vec![0.0f64; 1000];
+///             file.read_channel(&ChannelPath::new("group", channel), &mut output)
+///                 .unwrap();
+///             output
+///         })
+///     })
+///     .collect();
+/// ```
+#[derive(Debug)]
+pub struct SharedTdmsFile {
+    index: Index,
+    file: File,
+}
+
+impl SharedTdmsFile {
+    /// Open the file from the path for reading and index the metadata.
+    pub fn open(path: &Path) -> Result<Self, TdmsError> {
+        let mut file = File::options().read(true).open(path)?;
+        let index = super::build_index(&mut file)?;
+        Ok(Self { index, file })
+    }
+
+    /// Get the length of the channel.
+    pub fn channel_length(&self, channel: &ChannelPath) -> Option<u64> {
+        self.index.channel_length(channel)
+    }
+
+    /// Read the property by name from the full object path.
+    /// This will return `None` if the property does not exist.
+    pub fn read_property(
+        &self,
+        object_path: &PropertyPath,
+        property: &str,
+    ) -> Result<Option<&PropertyValue>, TdmsError> {
+        self.index.get_object_property(object_path, property)
+    }
+
+    /// Read a single channel from the file.
+    ///
+    /// See [`TdmsFile::read_channel`].
+    pub fn read_channel<D: TdmsStorageType>(
+        &self,
+        channel: &ChannelPath,
+        output: &mut [D],
+    ) -> Result<(), TdmsError> {
+        self.read_channel_from(channel, 0, output)
+    }
+
+    /// Read a single channel from the file starting at a specific sample position.
+    ///
+    /// See [`TdmsFile::read_channel_from`].
+    pub fn read_channel_from<D: TdmsStorageType>(
+        &self,
+        channel: &ChannelPath,
+        start: u64,
+        output: &mut [D],
+    ) -> Result<(), TdmsError> {
+        self.read_channels_from(&[channel], start, &mut [output])
+    }
+
+    /// Read multiple channels from the file.
+    ///
+    /// See [`TdmsFile::read_channels`].
+    pub fn read_channels<D: TdmsStorageType>(
+        &self,
+        channels: &[impl AsRef<ChannelPath>],
+        output: &mut [&mut [D]],
+    ) -> Result<(), TdmsError> {
+        self.read_channels_from(channels, 0, output)
+    }
+
+    /// Read multiple channels from the file starting at a specific sample position.
+    ///
+    /// See [`TdmsFile::read_channels_from`].
+    pub fn read_channels_from<D: TdmsStorageType>(
+        &self,
+        channels: &[impl AsRef<ChannelPath>],
+        start: u64,
+        output: &mut [&mut [D]],
+    ) -> Result<(), TdmsError> {
+        let plan = plan_channels_read(&self.index, channels, start)?;
+        let mut reader = PositionalReader::new(&self.file);
+        execute_read_plan(&self.index, &mut reader, plan, output)
+    }
+}
+
+impl TdmsFile<File> {
+    /// Convert into a [`SharedTdmsFile`] which can be read from multiple threads.
+    ///
+    /// This keeps the existing index so the file isn't indexed again.
+    pub fn into_shared(self) -> SharedTdmsFile {
+        SharedTdmsFile {
+            index: self.index,
+            file: self.file,
+        }
+    }
+}
+
+/// Provides [`Read`] and [`Seek`] over a shared file using positional reads.
+///
+/// The position is local to this reader so the OS file cursor is never used.
+pub(super) struct PositionalReader<'a> {
+    file: &'a File,
+    position: u64,
+}
+
+impl<'a> PositionalReader<'a> {
+    pub(super) fn new(file: &'a File) -> Self {
+        Self { file, position: 0 }
+    }
+}
+
+impl Read for PositionalReader<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        #[cfg(unix)]
+        let read = std::os::unix::fs::FileExt::read_at(self.file, buf, self.position)?;
+        #[cfg(windows)]
+        let read = std::os::windows::fs::FileExt::seek_read(self.file, buf, self.position)?;
+        self.position += read as u64;
+        Ok(read)
+    }
+}
+
+impl Seek for PositionalReader<'_> {
+    fn seek(&mut self, pos: SeekFrom) -> std::io::Result<u64> {
+        let new_position = match pos {
+            SeekFrom::Start(offset) => Some(offset),
+            SeekFrom::Current(offset) => self.position.checked_add_signed(offset),
+            SeekFrom::End(offset) => self.file.metadata()?.len().checked_add_signed(offset),
+        };
+        self.position = new_position.ok_or_else(|| {
+            std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                "invalid seek to a negative or overflowing position",
+            )
+        })?;
+        Ok(self.position)
+    }
+
+    fn stream_position(&mut self) -> std::io::Result<u64> {
+        Ok(self.position)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn positional_reader_seeks_independently() {
+        let mut path = std::env::temp_dir();
+        path.push(format!("tedium-{}-positional.bin", std::process::id()));
+        std::fs::write(&path, [0u8, 1, 2, 3, 4, 5, 6, 7]).unwrap();
+        let file = File::open(&path).unwrap();
+
+        let mut reader1 = PositionalReader::new(&file);
+        let mut reader2 = PositionalReader::new(&file);
+        let mut buf = [0u8; 2];
+
+        reader1.seek(SeekFrom::Start(4)).unwrap();
+        reader1.read_exact(&mut buf).unwrap();
+        assert_eq!(buf, [4, 5]);
+
+        reader2.read_exact(&mut buf).unwrap();
+        assert_eq!(buf, [0, 1]);
+
+        reader1.seek(SeekFrom::Current(-4)).unwrap();
+        reader1.read_exact(&mut buf).unwrap();
+        assert_eq!(buf, [2, 3]);
+
+        assert_eq!(reader2.seek(SeekFrom::End(-1)).unwrap(), 7);
+        assert!(reader2.seek(SeekFrom::Current(-10)).is_err());
+
+        drop(file);
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,533 @@
+//! Pluggable storage for TDMS files.
+//!
+//! [`crate::TdmsFile`] reads through a [`Read`] + [`Seek`] stream which suits local files.
+//! Remote stores such as object stores or HTTP servers are better accessed with
+//! byte range requests instead. [`TdmsStorage`] describes storage in those terms and
+//! [`StorageStream`] adapts it so a [`TdmsFile`] can read it as ranges. Only the metadata
+//! and the data blocks actually needed are fetched.
+
+#[cfg(any(unix, windows))]
+use std::fs::File;
+use std::io::{Cursor, Read, Seek, SeekFrom, Write};
+#[cfg(any(unix, windows))]
+use std::path::Path;
+
+use super::TdmsFile;
+use super::channel_reader::MAX_SPAN_BYTES;
+use crate::error::TdmsError;
+use crate::index::Index;
+use crate::meta_data::{LEAD_IN_BYTES, Segment};
+
+/// Storage which can be read in byte ranges and appended to.
+///
+/// Implement this to read TDMS files from other sources such as object stores
+/// with [`TdmsFile::from_storage`].
+pub trait TdmsStorage {
+    /// Read `length` bytes starting at `offset`.
+    ///
+    /// This should only return fewer bytes than requested when the range runs
+    /// past the end of the storage.
+    fn read_range(&self, offset: u64, length: u64) -> Result<Vec<u8>, TdmsError>;
+
+    /// Add the bytes to the end of the storage.
+    ///
+    /// Storage which can't be written can return an error.
+    fn append(&mut self, bytes: &[u8]) -> Result<(), TdmsError>;
+}
+
+/// Storage held in memory.
+///
+/// # Example
+///
+/// ```rust
+/// use tedium::{ChannelPath, DataLayout, MemoryStorage, TdmsFile};
+///
+/// let mut file = TdmsFile::from_storage(MemoryStorage::new()).unwrap();
+/// let channel = ChannelPath::new("group", "channel");
+/// let mut writer = file.writer().unwrap();
+/// writer.write_channels(&[&channel], &[1.0, 2.0, 3.0], DataLayout::Contigious).unwrap();
+/// writer.sync().unwrap();
+/// drop(writer);
+///
+/// let mut output = [0.0f64; 3];
+/// file.read_channel(&channel, &mut output).unwrap();
+/// assert_eq!(output, [1.0, 2.0, 3.0]);
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MemoryStorage {
+    bytes: Vec<u8>,
+}
+
+impl MemoryStorage {
+    /// Create empty storage.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Get the stored bytes.
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+
+    /// Get the stored bytes, consuming the storage.
+    pub fn into_inner(self) -> Vec<u8> {
+        self.bytes
+    }
+}
+
+impl From<Vec<u8>> for MemoryStorage {
+    fn from(bytes: Vec<u8>) -> Self {
+        Self { bytes }
+    }
+}
+
+impl TdmsStorage for MemoryStorage {
+    fn read_range(&self, offset: u64, length: u64) -> Result<Vec<u8>, TdmsError> {
+        let start = (offset as usize).min(self.bytes.len());
+        let end = start.saturating_add(length as usize).min(self.bytes.len());
+        Ok(self.bytes[start..end].to_vec())
+    }
+
+    fn append(&mut self, bytes: &[u8]) -> Result<(), TdmsError> {
+        self.bytes.extend_from_slice(bytes);
+        Ok(())
+    }
+}
+
+/// Storage in a file on the local filesystem.
+///
+/// Ranges are read with positional reads so reads only need `&self`.
+#[cfg(any(unix, windows))]
+#[derive(Debug)]
+pub struct FileStorage {
+    file: File,
+}
+
+#[cfg(any(unix, windows))]
+impl FileStorage {
+    /// Use an open file as storage.
+    ///
+    /// The file must be readable, and writable if it will be appended to.
+    pub fn new(file: File) -> Self {
+        Self { file }
+    }
+
+    /// Open the file at the path for reading and appending.
+    pub fn open(path: &Path) -> Result<Self, TdmsError> {
+        let file = File::options().read(true).append(true).open(path)?;
+        Ok(Self::new(file))
+    }
+
+    /// Open the file at the path for reading only.
+    ///
+    /// Appending to this storage will fail.
+    pub fn open_read_only(path: &Path) -> Result<Self, TdmsError> {
+        Ok(Self::new(File::open(path)?))
+    }
+
+    /// Create a new file at the path. This will replace any existing file.
+    pub fn create(path: &Path) -> Result<Self, TdmsError> {
+        let file = File::options()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .open(path)?;
+        Ok(Self::new(file))
+    }
+
+    /// Get the underlying file.
+    pub fn into_inner(self) -> File {
+        self.file
+    }
+}
+
+#[cfg(any(unix, windows))]
+impl TdmsStorage for FileStorage {
+    fn read_range(&self, offset: u64, length: u64) -> Result<Vec<u8>, TdmsError> {
+        let mut reader = super::shared_reader::PositionalReader::new(&self.file);
+        reader.seek(SeekFrom::Start(offset))?;
+        let mut bytes = Vec::new();
+        bytes
+            .try_reserve_exact(length as usize)
+            .map_err(|_| TdmsError::VecAllocationFailed)?;
+        reader.take(length).read_to_end(&mut bytes)?;
+        Ok(bytes)
+    }
+
+    fn append(&mut self, bytes: &[u8]) -> Result<(), TdmsError> {
+        self.file.seek(SeekFrom::End(0))?;
+        self.file.write_all(bytes)?;
+        Ok(())
+    }
+}
+
+/// The number of bytes loaded by each read from the storage unless more are asked for.
+///
+/// This matches the largest span the read planner loads in one go, so a span of
+/// neighbouring blocks becomes a single range request.
+const READ_AHEAD_BYTES: u64 = MAX_SPAN_BYTES;
+
+/// Provides [`Read`], [`Seek`] and [`Write`] over a [`TdmsStorage`] so it can be used by [`TdmsFile`].
+///
+/// Reads load a range of at least 1 MiB from the position and serve later
+/// reads from it until they move outside the range. Reading a channel therefore only loads
+/// the ranges holding its data, and never more than one range at a time.
+///
+/// Writes are appended to the storage so they are only allowed at the end of the stream.
+#[derive(Debug)]
+pub struct StorageStream<S: TdmsStorage> {
+    storage: S,
+    position: u64,
+    /// The length of the TDMS data in the storage.
+    end: u64,
+    loaded_start: u64,
+    loaded: Vec<u8>,
+}
+
+impl<S: TdmsStorage> StorageStream<S> {
+    fn new(storage: S, end: u64) -> Self {
+        Self {
+            storage,
+            position: 0,
+            end,
+            loaded_start: 0,
+            loaded: Vec::new(),
+        }
+    }
+}
+
+fn into_io_error(error: TdmsError) -> std::io::Error {
+    match error {
+        TdmsError::IoError(error) => error,
+        error => std::io::Error::other(error),
+    }
+}
+
+impl<S: TdmsStorage> Read for StorageStream<S> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let loaded_end = self.loaded_start + self.loaded.len() as u64;
+        if self.position < self.loaded_start || self.position >= loaded_end {
+            let length = (buf.len() as u64).max(READ_AHEAD_BYTES);
+            self.loaded = self
+                .storage
+                .read_range(self.position, length)
+                .map_err(into_io_error)?;
+            self.loaded_start = self.position;
+        }
+
+        let available = &self.loaded[(self.position - self.loaded_start) as usize..];
+        let count = available.len().min(buf.len());
+        buf[..count].copy_from_slice(&available[..count]);
+        self.position += count as u64;
+        Ok(count)
+    }
+}
+
+impl<S: TdmsStorage> Seek for StorageStream<S> {
+    fn seek(&mut self, pos: SeekFrom) -> std::io::Result<u64> {
+        let position = match pos {
+            SeekFrom::Start(position) => Some(position),
+            SeekFrom::Current(offset) => self.position.checked_add_signed(offset),
+            SeekFrom::End(offset) => self.end.checked_add_signed(offset),
+        };
+        self.position = position.ok_or_else(|| {
+            std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                "seek to a negative or overflowing position",
+            )
+        })?;
+        Ok(self.position)
+    }
+}
+
+impl<S: TdmsStorage> Write for StorageStream<S> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        if self.position != self.end {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::Unsupported,
+                "storage can only be written by appending to the end",
+            ));
+        }
+        self.storage.append(buf).map_err(into_io_error)?;
+        self.end += buf.len() as u64;
+        self.position = self.end;
+        // The loaded range may have been cut short by the old end.
+        self.loaded.clear();
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+/// A TDMS file accessed through a [`TdmsStorage`].
+///
+/// This is a [`TdmsFile`] so it has all the same read and write methods.
+/// Reads fetch only the ranges containing the blocks they need, merging neighbouring
+/// blocks into a single range.
+///
+/// Create it with [`TdmsFile::from_storage`].
+pub type StorageTdmsFile<S> = TdmsFile<StorageStream<S>>;
+
+impl<S: TdmsStorage> TdmsFile<StorageStream<S>> {
+    /// Index the TDMS data in the storage.
+    ///
+    /// Only the segment headers are read to build the index.
+    ///
+    /// Returns [`TdmsError::StorageLengthMismatch`] if the storage has bytes after the
+    /// last complete segment or ends before the last segment does, such as when a segment
+    /// was only partly written. Appending to it would write segments at different offsets
+    /// from those in the index.
+    pub fn from_storage(storage: S) -> Result<Self, TdmsError> {
+        let index = build_index_from_storage(&storage)?;
+        let end = index.indexed_length();
+        let has_trailing_bytes = !storage.read_range(end, 1)?.is_empty();
+        let is_truncated = end > 0 && storage.read_range(end - 1, 1)?.is_empty();
+        if has_trailing_bytes || is_truncated {
+            return Err(TdmsError::StorageLengthMismatch);
+        }
+        Ok(Self {
+            index,
+            file: StorageStream::new(storage, end),
+            cache: None,
+        })
+    }
+
+    /// Get a reference to the storage.
+    pub fn storage(&self) -> &S {
+        &self.file.storage
+    }
+
+    /// Get the storage, consuming the file.
+    pub fn into_storage(self) -> S {
+        self.file.storage
+    }
+}
+
+/// Scan the segments in the storage to build the index.
+///
+/// Each segment needs a read for the lead in and another for the metadata if it has any.
+fn build_index_from_storage(storage: &impl TdmsStorage) -> Result<Index, TdmsError> {
+    let mut index = Index::new();
+    let mut position = 0;
+
+    loop {
+        let mut header = storage.read_range(position, LEAD_IN_BYTES)?;
+        // A short lead in is left for the segment reader to report.
+        if let Ok(lead_in) = <&[u8; LEAD_IN_BYTES as usize]>::try_from(&header[..]) {
+            let header_length = Segment::header_length(lead_in)?;
+            if header_length > LEAD_IN_BYTES {
+                let meta_data =
+                    storage.read_range(position + LEAD_IN_BYTES, header_length - LEAD_IN_BYTES)?;
+                header.extend_from_slice(&meta_data);
+            }
+        }
+
+        match Segment::read(&mut Cursor::new(header)) {
+            Ok(segment) => position = index.add_segment(segment)?,
+            Err(TdmsError::EndOfFile) => break,
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(index)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+
+    use super::*;
+    use crate::{ChannelPath, DataLayout, PropertyPath, PropertyValue};
+
+    /// Records the ranges read from the inner storage.
+    struct RecordingStorage {
+        inner: MemoryStorage,
+        reads: RefCell<Vec<(u64, u64)>>,
+    }
+
+    impl TdmsStorage for RecordingStorage {
+        fn read_range(&self, offset: u64, length: u64) -> Result<Vec<u8>, TdmsError> {
+            self.reads.borrow_mut().push((offset, length));
+            self.inner.read_range(offset, length)
+        }
+
+        fn append(&mut self, bytes: &[u8]) -> Result<(), TdmsError> {
+            self.inner.append(bytes)
+        }
+    }
+
+    fn channels() -> [ChannelPath; 2] {
+        [
+            ChannelPath::new("group", "ch1"),
+            ChannelPath::new("group", "ch2"),
+        ]
+    }
+
+    fn build_storage(layout: DataLayout) -> MemoryStorage {
+        let mut file = TdmsFile::from_storage(MemoryStorage::new()).unwrap();
+        let mut writer = file.writer().unwrap();
+        writer
+            .write_properties(
+                &PropertyPath::group("group"),
+                &[("unit", PropertyValue::String("V".to_string()))],
+            )
+            .unwrap();
+        for block in 0..10 {
+            let values: Vec<f64> = (0..200).map(|i| (block * 1000 + i) as f64).collect();
+            writer.write_channels(&channels(), &values, layout).unwrap();
+        }
+        writer.sync().unwrap();
+        drop(writer);
+        file.into_storage()
+    }
+
+    #[test]
+    fn memory_storage_reads_are_clamped() {
+        let storage = MemoryStorage::from(vec![0, 1, 2, 3]);
+        assert_eq!(storage.read_range(2, 10).unwrap(), vec![2, 3]);
+        assert_eq!(storage.read_range(10, 10).unwrap(), Vec::<u8>::new());
+        assert_eq!(storage.read_range(1, u64::MAX).unwrap(), vec![1, 2, 3]);
+    }
+
+    #[test]
+    fn storage_reads_match_tdms_file() {
+        for layout in [DataLayout::Contigious, DataLayout::Interleaved] {
+            let storage = build_storage(layout);
+            let mut tdms_file = TdmsFile::new(Cursor::new(storage.as_bytes().to_vec())).unwrap();
+            let mut file = TdmsFile::from_storage(storage).unwrap();
+
+            let mut expected1 = vec![0.0f64; 700];
+            let mut expected2 = vec![0.0f64; 700];
+            tdms_file
+                .read_channels_from(&channels(), 150, &mut [&mut expected1, &mut expected2])
+                .unwrap();
+
+            let mut output1 = vec![0.0f64; 700];
+            let mut output2 = vec![0.0f64; 700];
+            file.read_channels_from(&channels(), 150, &mut [&mut output1, &mut output2])
+                .unwrap();
+
+            assert_eq!(output1, expected1);
+            assert_eq!(output2, expected2);
+            assert_eq!(
+                file.read_property(&PropertyPath::group("group"), "unit")
+                    .unwrap(),
+                Some(&PropertyValue::String("V".to_string()))
+            );
+        }
+    }
+
+    #[test]
+    fn reads_only_needed_ranges() {
+        let storage = RecordingStorage {
+            inner: build_storage(DataLayout::Contigious),
+            reads: RefCell::new(Vec::new()),
+        };
+        let mut file = TdmsFile::from_storage(storage).unwrap();
+        file.storage().reads.borrow_mut().clear();
+
+        // Read from the last 2 blocks. These are small so they are merged into one range.
+        let mut output = vec![0.0f64; 200];
+        file.read_channel_from(&ChannelPath::new("group", "ch1"), 800, &mut output)
+            .unwrap();
+        assert_eq!(output[0], 8000.0);
+        assert_eq!(output[199], 9099.0);
+
+        let reads = file.storage().reads.borrow();
+        assert_eq!(reads.len(), 1);
+        let block = file.index.get_data_block(8).unwrap();
+        assert_eq!(reads[0].0, block.start);
+    }
+
+    #[test]
+    fn large_blocks_are_read_in_ranges() {
+        // A single contiguous block of 2 channels larger than the read ahead.
+        let mut file = TdmsFile::from_storage(MemoryStorage::new()).unwrap();
+        let values: Vec<f64> = (0..400_000).map(|i| i as f64).collect();
+        let mut writer = file.writer().unwrap();
+        writer
+            .write_channels(&channels(), &values, DataLayout::Contigious)
+            .unwrap();
+        writer.sync().unwrap();
+        drop(writer);
+
+        let storage = RecordingStorage {
+            inner: file.into_storage(),
+            reads: RefCell::new(Vec::new()),
+        };
+        let mut file = TdmsFile::from_storage(storage).unwrap();
+        file.storage().reads.borrow_mut().clear();
+
+        let mut output = vec![0.0f64; 1000];
+        file.read_channel_from(&channels()[1], 100_000, &mut output)
+            .unwrap();
+        assert_eq!(output[0], 300_000.0);
+
+        // Only the range holding the samples is read.
+        let reads = file.storage().reads.borrow();
+        let block = file.index.get_data_block(0).unwrap();
+        assert_eq!(reads.len(), 1);
+        assert_eq!(
+            reads[0],
+            (block.start + (200_000 + 100_000) * 8, READ_AHEAD_BYTES)
+        );
+    }
+
+    #[test]
+    fn writes_must_append() {
+        let mut stream = StorageStream::new(MemoryStorage::from(vec![0, 1, 2, 3]), 4);
+        stream.seek(SeekFrom::Start(2)).unwrap();
+        assert!(stream.write_all(&[9]).is_err());
+
+        stream.seek(SeekFrom::End(0)).unwrap();
+        stream.write_all(&[4, 5]).unwrap();
+        stream.seek(SeekFrom::Start(3)).unwrap();
+        let mut bytes = Vec::new();
+        stream.read_to_end(&mut bytes).unwrap();
+        assert_eq!(bytes, vec![3, 4, 5]);
+    }
+
+    #[test]
+    fn appends_to_existing_data() {
+        let storage = build_storage(DataLayout::Interleaved);
+        let mut file = TdmsFile::from_storage(storage).unwrap();
+        let mut writer = file.writer().unwrap();
+        writer
+            .write_channels(&channels(), &[-1.0, -2.0], DataLayout::Interleaved)
+            .unwrap();
+        writer.sync().unwrap();
+        drop(writer);
+
+        let mut file = TdmsFile::from_storage(file.into_storage()).unwrap();
+        assert_eq!(file.channel_length(&channels()[0]), Some(1001));
+        let mut output = [0.0f64; 1];
+        file.read_channel_from(&channels()[1], 1000, &mut output)
+            .unwrap();
+        assert_eq!(output, [-2.0]);
+    }
+
+    #[test]
+    fn trailing_data_is_rejected() {
+        // Too short to be read as the lead in of another segment.
+        let mut bytes = build_storage(DataLayout::Interleaved).into_inner();
+        bytes.extend_from_slice(b"TDS");
+        let result = TdmsFile::from_storage(MemoryStorage::from(bytes));
+        assert!(matches!(result, Err(TdmsError::StorageLengthMismatch)));
+    }
+
+    #[test]
+    fn truncated_segment_is_rejected() {
+        let mut bytes = build_storage(DataLayout::Interleaved).into_inner();
+        bytes.truncate(bytes.len() - 8);
+        let result = TdmsFile::from_storage(MemoryStorage::from(bytes));
+        assert!(matches!(result, Err(TdmsError::StorageLengthMismatch)));
+    }
+
+    #[test]
+    fn empty_storage_has_no_channels() {
+        let file = TdmsFile::from_storage(MemoryStorage::new()).unwrap();
+        assert_eq!(file.channel_length(&channels()[0]), None);
+    }
+}
@@ -0,0 +1,228 @@
+//! An owned tree of the groups and channels in a file.
+
+use std::collections::BTreeMap;
+use std::io::{Read, Seek};
+
+use super::TdmsFile;
+use crate::index::Index;
+use crate::io::data_types::DataType;
+use crate::{ChannelPath, PropertyPath, PropertyValue};
+
+/// The structure of a TDMS file with its groups, channels and their properties.
+///
+/// This is a snapshot of the file when it was created and doesn't change if more
+/// data is written.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct FileStructure {
+    /// The properties of the file object.
+    pub properties: BTreeMap<String, PropertyValue>,
+    /// The groups in the file ordered by name.
+    pub groups: Vec<GroupStructure>,
+}
+
+/// A group in a [`FileStructure`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct GroupStructure {
+    pub name: String,
+    pub path: PropertyPath,
+    /// The group properties.
+    ///
+    /// This is empty if the group is only implied by the path of its channels.
+    pub properties: BTreeMap<String, PropertyValue>,
+    /// The channels in the group ordered by name.
+    pub channels: Vec<ChannelStructure>,
+}
+
+/// A channel in a [`FileStructure`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChannelStructure {
+    pub name: String,
+    pub path: ChannelPath,
+    /// The data type of the most recent data, or `None` if the channel has never had a type.
+    pub data_type: Option<DataType>,
+    /// The total number of samples in the channel.
+    pub length: u64,
+    pub properties: BTreeMap<String, PropertyValue>,
+}
+
+impl GroupStructure {
+    fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            path: PropertyPath::group(name),
+            properties: BTreeMap::new(),
+            channels: Vec::new(),
+        }
+    }
+}
+
+impl<F: Read + Seek> TdmsFile<F> {
+    /// Build the tree of groups and channels in the file with their properties.
+    ///
+    /// Groups which have no object of their own in the file but are part of a channel
+    /// path are included with no properties, matching [`Self::list_groups`].
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use tedium::{TdmsFile, ChannelPath, DataLayout};
+    ///
+    /// let mut file = TdmsFile::new(std::io::Cursor::new(vec![])).unwrap();
+    /// let mut writer = file.writer().unwrap();
+    /// writer
+    ///     .write_channels(&[ChannelPath::new("group", "channel")], &[1.0, 2.0], DataLayout::Contigious)
+    ///     .unwrap();
+    /// drop(writer);
+    ///
+    /// for group in file.structure().groups {
+    ///     for channel in group.channels {
+    ///         println!("{}/{}: {} samples", group.name, channel.name, channel.length);
+    ///     }
+    /// }
+    /// ```
+    pub fn structure(&self) -> FileStructure {
+        build_structure(&self.index)
+    }
+}
+
+fn build_structure(index: &Index) -> FileStructure {
+    let mut structure = FileStructure::default();
+    let mut groups: BTreeMap<String, GroupStructure> = BTreeMap::new();
+
+    // Paths which don't parse can't be reached through the rest of the API so skip them.
+    for path in index
+        .all_paths()
+        .filter_map(|path| PropertyPath::try_from(path).ok())
+    {
+        match (path.group_name(), path.channel_name()) {
+            (None, _) => structure.properties = object_properties(index, &path),
+            (Some(group), None) => {
+                groups
+                    .entry(group.to_string())
+                    .or_insert_with(|| GroupStructure::new(group))
+                    .properties = object_properties(index, &path);
+            }
+            (Some(group), Some(channel)) => {
+                let channel_path = ChannelPath::new(group, channel);
+                // The channel info already holds a copy of the properties.
+                let (data_type, length, properties) = match index.channel_info(&channel_path) {
+                    Some(info) => (info.data_type, info.length, info.properties),
+                    None => (None, 0, BTreeMap::new()),
+                };
+                groups
+                    .entry(group.to_string())
+                    .or_insert_with(|| GroupStructure::new(group))
+                    .channels
+                    .push(ChannelStructure {
+                        name: channel.to_string(),
+                        data_type,
+                        length,
+                        path: channel_path,
+                        properties,
+                    });
+            }
+        }
+    }
+
+    structure.groups = groups.into_values().collect();
+    for group in structure.groups.iter_mut() {
+        group
+            .channels
+            .sort_by(|left, right| left.name.cmp(&right.name));
+    }
+    structure
+}
+
+fn object_properties(index: &Index, path: &PropertyPath) -> BTreeMap<String, PropertyValue> {
+    index
+        .get_object_properties(path)
+        .map(|properties| {
+            properties
+                .map(|(name, value)| (name.clone(), value.clone()))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use super::*;
+    use crate::DataLayout;
+
+    #[test]
+    fn builds_tree_with_implicit_groups() {
+        let mut file = TdmsFile::new(Cursor::new(Vec::new())).unwrap();
+        let mut writer = file.writer().unwrap();
+        writer
+            .write_properties(
+                &PropertyPath::file(),
+                &[("name", PropertyValue::String("test".to_string()))],
+            )
+            .unwrap();
+        writer
+            .write_properties(
+                &PropertyPath::group("b"),
+                &[("order", PropertyValue::I32(2))],
+            )
+            .unwrap();
+        writer
+            .write_channels(
+                &[ChannelPath::new("b", "y"), ChannelPath::new("b", "x")],
+                &[1i32, 2, 3, 4],
+                DataLayout::Contigious,
+            )
+            .unwrap();
+        writer
+            .write_channels(
+                &[ChannelPath::new("a", "z")],
+                &[1.0f64, 2.0, 3.0],
+                DataLayout::Contigious,
+            )
+            .unwrap();
+        writer
+            .write_properties(
+                &PropertyPath::channel("b", "x"),
+                &[("unit", PropertyValue::String("V".to_string()))],
+            )
+            .unwrap();
+        drop(writer);
+
+        let structure = file.structure();
+        assert_eq!(
+            structure.properties.get("name"),
+            Some(&PropertyValue::String("test".to_string()))
+        );
+
+        let names: Vec<_> = structure.groups.iter().map(|g| g.name.as_str()).collect();
+        assert_eq!(names, ["a", "b"]);
+        let listed: Vec<_> = file.list_groups().collect();
+        assert_eq!(names, listed);
+
+        let a = &structure.groups[0];
+        assert!(a.properties.is_empty());
+        assert_eq!(a.path, PropertyPath::group("a"));
+        assert_eq!(a.channels.len(), 1);
+        assert_eq!(a.channels[0].data_type, Some(DataType::DoubleFloat));
+        assert_eq!(a.channels[0].length, 3);
+
+        let b = &structure.groups[1];
+        assert_eq!(b.properties.get("order"), Some(&PropertyValue::I32(2)));
+        let channels: Vec<_> = b.channels.iter().map(|c| c.name.as_str()).collect();
+        assert_eq!(channels, ["x", "y"]);
+        assert_eq!(b.channels[0].path, ChannelPath::new("b", "x"));
+        assert_eq!(b.channels[0].data_type, Some(DataType::I32));
+        assert_eq!(b.channels[0].length, 2);
+        assert_eq!(
+            b.channels[0].properties.get("unit"),
+            Some(&PropertyValue::String("V".to_string()))
+        );
+    }
+
+    #[test]
+    fn empty_file_has_empty_structure() {
+        let file = TdmsFile::new(Cursor::new(Vec::new())).unwrap();
+        assert_eq!(file.structure(), FileStructure::default());
+    }
+}
@@ -11,7 +11,7 @@ use crate::{
     raw_data::DataBlock,
 };
 
-use super::{DataFormat, DataLocation, ObjectData, ObjectIndex};
+use super::{DataFormat, DataLocation, ObjectData, ObjectIndex, SegmentRecord};
 
 /// Data cached for the current "active" objects which are the objects
 /// that we are expecting data in the next data block.
@@ -79,6 +79,7 @@ impl super::Index {
             }
         }
 
+        let mut data_block_index = None;
         if segment.toc.contains_raw_data {
             let active_data_channels = self.get_active_raw_data_meta();
 
@@ -90,13 +91,21 @@ impl super::Index {
                 DataBlock::from_segment(&segment, self.next_segment_start, active_data_channels)?;
 
             self.insert_data_block(data_block)?;
+            data_block_index = Some(self.data_blocks.len() - 1);
         }
 
+        let segment_start = self.next_segment_start;
         let segment_size = segment.total_size_bytes()?;
         match self.next_segment_start.checked_add(segment_size) {
             Some(next_segment_start) => self.next_segment_start = next_segment_start,
             None => return Err(TdmsError::SegmentAddressOverflow),
         }
+        self.record_property_history(&segment, self.segments.len());
+        self.segments.push(SegmentRecord {
+            offset: segment_start,
+            toc: segment.toc,
+            data_block: data_block_index,
+        });
         Ok(self.next_segment_start)
     }
 
@@ -0,0 +1,411 @@
+//! Read only views of how the indexed file is laid out.
+//!
+//! These are intended for debugging files and understanding how they were written
+//! rather than reading data, so they are built on request from the index.
+
+use std::collections::BTreeMap;
+
+use super::DataFormat;
+use crate::PropertyValue;
+use crate::io::data_types::DataType;
+use crate::meta_data::{LEAD_IN_BYTES, RawDataIndex, RawDataMeta, Segment, ToC};
+use crate::paths::ChannelPath;
+use crate::raw_data::{DataLayout, Endianess};
+
+/// A summary of a single segment in the file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SegmentInfo {
+    /// The position of the start of the segment lead in.
+    pub offset: u64,
+    /// The table of contents flags for the segment.
+    pub toc: ToC,
+    /// The length of the segment after the lead in, as recorded in the lead in.
+    pub next_segment_offset: u64,
+    /// The length of the metadata, as recorded in the lead in.
+    pub raw_data_offset: u64,
+    /// The objects listed in the segment metadata in the order they were written.
+    ///
+    /// This is empty if the segment has no metadata.
+    pub objects: Vec<SegmentObject>,
+    /// The index of the data block holding the raw data of this segment.
+    pub data_block: Option<usize>,
+}
+
+impl SegmentInfo {
+    pub(crate) fn from_segment(segment: &Segment, offset: u64, data_block: Option<usize>) -> Self {
+        let objects = segment
+            .meta_data
+            .iter()
+            .flat_map(|meta| meta.objects.iter())
+            .map(|object| SegmentObject {
+                path: object.path.clone(),
+                raw_data_index: object.raw_data_index.clone(),
+                property_count: object.properties.len(),
+            })
+            .collect();
+
+        Self {
+            offset,
+            toc: segment.toc,
+            next_segment_offset: segment.next_segment_offset,
+            raw_data_offset: segment.raw_data_offset,
+            objects,
+            data_block,
+        }
+    }
+
+    /// The position in the file where the raw data starts.
+    pub fn raw_data_start(&self) -> u64 {
+        self.offset + LEAD_IN_BYTES + self.raw_data_offset
+    }
+
+    /// The number of bytes of raw data in the segment.
+    pub fn raw_data_size(&self) -> u64 {
+        self.next_segment_offset
+            .saturating_sub(self.raw_data_offset)
+    }
+}
+
+/// The position and flags of a segment, kept in the index so it can be found again.
+///
+/// The full [`SegmentInfo`] is built from the file when it is requested.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct SegmentRecord {
+    /// The position of the start of the segment lead in.
+    pub(crate) offset: u64,
+    pub(crate) toc: ToC,
+    /// The index of the data block holding the raw data of this segment.
+    pub(crate) data_block: Option<usize>,
+}
+
+/// An object entry in the metadata of a segment.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SegmentObject {
+    /// The full TDMS path of the object.
+    pub path: String,
+    /// The raw data index written for the object.
+    pub raw_data_index: RawDataIndex,
+    /// The number of properties written for the object in this segment.
+    pub property_count: usize,
+}
+
+/// A summary of a data block in the index.
+///
+/// Each segment with raw data has its own data block.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DataBlockInfo {
+    /// The position in the file where the raw data starts.
+    pub start: u64,
+    /// The length of the raw data in bytes.
+    pub length: u64,
+    pub layout: DataLayout,
+    pub byte_order: Endianess,
+    /// The number of repeated chunks of the channel list in the block.
+    pub number_of_chunks: usize,
+    /// The channels in the order they are stored in each chunk.
+    pub channels: Vec<BlockChannel>,
+}
+
+/// A channel stored in a data block.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BlockChannel {
+    /// The full TDMS path of the channel.
+    pub path: String,
+    /// The format of the channel data in each chunk.
+    pub format: RawDataMeta,
+}
+
+/// A description of a channel and its data.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChannelInfo {
+    /// The data type of the most recent data, or `None` if the channel has never had a type.
+    pub data_type: Option<DataType>,
+    /// True if the data type is not the same in every data block.
+    pub data_type_changed: bool,
+    /// The total number of samples in the channel.
+    pub length: u64,
+    /// The number of data blocks holding data for the channel.
+    pub number_of_blocks: usize,
+    /// The layouts used by the blocks holding the channel data, in the order first seen.
+    pub layouts: Vec<DataLayout>,
+    /// The byte orders used by the blocks holding the channel data, in the order first seen.
+    pub byte_orders: Vec<Endianess>,
+    /// The current value of each property on the channel.
+    pub properties: BTreeMap<String, PropertyValue>,
+}
+
+impl super::Index {
+    /// Get the segments which have been indexed in file order.
+    pub(crate) fn segments(&self) -> &[SegmentRecord] {
+        &self.segments
+    }
+
+    /// Get a description of the channel.
+    ///
+    /// Returns `None` if the channel doesn't exist.
+    pub(crate) fn channel_info(&self, path: &ChannelPath) -> Option<ChannelInfo> {
+        let object = self.objects.get(path.path())?;
+
+        let mut data_types = Vec::new();
+        let mut layouts = Vec::new();
+        let mut byte_orders = Vec::new();
+        for location in &object.data_locations {
+            let Some(block) = self.data_blocks.get(location.data_block) else {
+                continue;
+            };
+            if let Some(format) = block.channels.get(location.channel_index) {
+                push_unique(&mut data_types, format.data_type);
+            }
+            push_unique(&mut layouts, block.layout);
+            push_unique(&mut byte_orders, block.byte_order);
+        }
+
+        let data_type = object
+            .latest_data_format
+            .as_ref()
+            .map(|format| match format {
+                DataFormat::RawData(meta) => meta.data_type,
+            });
+
+        Some(ChannelInfo {
+            data_type,
+            data_type_changed: data_types.len() > 1,
+            length: object
+                .data_locations
+                .iter()
+                .map(|location| location.number_of_samples)
+                .sum(),
+            number_of_blocks: object.data_locations.len(),
+            layouts,
+            byte_orders,
+            properties: object.properties.clone(),
+        })
+    }
+
+    /// The number of data blocks in the index.
+    pub(crate) fn data_block_count(&self) -> usize {
+        self.data_blocks.len()
+    }
+
+    /// Get a summary of the data block at the index.
+    ///
+    /// Returns `None` if the index is out of range.
+    pub(crate) fn data_block_info(&self, index: usize) -> Option<DataBlockInfo> {
+        let block = self.data_blocks.get(index)?;
+
+        //Find which object owns each channel slot in the block.
+        //Locations are added in block order so we can search them.
+        let mut paths = vec![None; block.channels.len()];
+        for object in self.objects.values() {
+            if let Ok(position) = object
+                .data_locations
+                .binary_search_by_key(&index, |location| location.data_block)
+            {
+                let location = &object.data_locations[position];
+                if let Some(path) = paths.get_mut(location.channel_index) {
+                    *path = Some(object.path.as_str());
+                }
+            }
+        }
+
+        let channels = block
+            .channels
+            .iter()
+            .zip(paths)
+            .map(|(format, path)| BlockChannel {
+                path: path.unwrap_or_default().to_string(),
+                format: format.clone(),
+            })
+            .collect();
+
+        Some(DataBlockInfo {
+            start: block.start,
+            length: block.length.get(),
+            layout: block.layout,
+            byte_order: block.byte_order,
+            number_of_chunks: block
+                .number_of_chunks()
+                .expect("chunks are validated when the block is indexed"),
+            channels,
+        })
+    }
+}
+
+fn push_unique<T: PartialEq>(values: &mut Vec<T>, value: T) {
+    if !values.contains(&value) {
+        values.push(value);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::index::{DataLocation, Index};
+    use crate::meta_data::{MetaData, ObjectMetaData};
+
+    fn raw_meta(data_type: DataType, number_of_values: u64) -> RawDataMeta {
+        RawDataMeta {
+            data_type,
+            number_of_values,
+            total_size_bytes: None,
+        }
+    }
+
+    fn first_segment() -> Segment {
+        Segment {
+            toc: ToC {
+                contains_meta_data: true,
+                contains_raw_data: true,
+                contains_new_object_list: true,
+                ..Default::default()
+            },
+            next_segment_offset: 500,
+            raw_data_offset: 20,
+            meta_data: Some(MetaData {
+                objects: vec![
+                    ObjectMetaData {
+                        path: "/'group'".to_string(),
+                        properties: vec![(
+                            "unit".to_string(),
+                            PropertyValue::String("V".to_string()),
+                        )],
+                        raw_data_index: RawDataIndex::None,
+                    },
+                    ObjectMetaData {
+                        path: "/'group'/'ch1'".to_string(),
+                        properties: vec![],
+                        raw_data_index: RawDataIndex::RawData(raw_meta(DataType::DoubleFloat, 20)),
+                    },
+                    ObjectMetaData {
+                        path: "/'group'/'ch2'".to_string(),
+                        properties: vec![],
+                        raw_data_index: RawDataIndex::RawData(raw_meta(DataType::I32, 20)),
+                    },
+                ],
+            }),
+        }
+    }
+
+    fn data_only_segment() -> Segment {
+        Segment {
+            toc: ToC {
+                contains_raw_data: true,
+                ..Default::default()
+            },
+            next_segment_offset: 480,
+            raw_data_offset: 0,
+            meta_data: None,
+        }
+    }
+
+    #[test]
+    fn records_segments_in_order() {
+        let mut index = Index::new();
+        index.add_segment(first_segment()).unwrap();
+        index.add_segment(data_only_segment()).unwrap();
+
+        let segments = index.segments();
+        assert_eq!(segments.len(), 2);
+
+        assert_eq!(segments[0].offset, 0);
+        assert_eq!(segments[0].toc, first_segment().toc);
+        assert_eq!(segments[0].data_block, Some(0));
+
+        assert_eq!(segments[1].offset, 528);
+        assert_eq!(segments[1].data_block, Some(1));
+    }
+
+    #[test]
+    fn segment_info_lists_objects() {
+        let segment = SegmentInfo::from_segment(&first_segment(), 0, Some(0));
+        assert_eq!(segment.raw_data_start(), 48);
+        assert_eq!(segment.raw_data_size(), 480);
+        assert_eq!(segment.objects.len(), 3);
+        assert_eq!(segment.objects[0].path, "/'group'");
+        assert_eq!(segment.objects[0].property_count, 1);
+        assert_eq!(segment.objects[0].raw_data_index, RawDataIndex::None);
+        assert_eq!(segment.data_block, Some(0));
+
+        let segment = SegmentInfo::from_segment(&data_only_segment(), 528, Some(1));
+        assert_eq!(segment.raw_data_start(), 556);
+        assert!(segment.objects.is_empty());
+    }
+
+    #[test]
+    fn data_block_info_names_channels() {
+        let mut index = Index::new();
+        index.add_segment(first_segment()).unwrap();
+
+        let info = index.data_block_info(0).unwrap();
+        assert_eq!(info.start, 48);
+        assert_eq!(info.length, 480);
+        assert_eq!(info.layout, DataLayout::Contigious);
+        assert_eq!(info.byte_order, Endianess::Little);
+        assert_eq!(info.number_of_chunks, 2);
+        assert_eq!(
+            info.channels,
+            vec![
+                BlockChannel {
+                    path: "/'group'/'ch1'".to_string(),
+                    format: raw_meta(DataType::DoubleFloat, 20),
+                },
+                BlockChannel {
+                    path: "/'group'/'ch2'".to_string(),
+                    format: raw_meta(DataType::I32, 20),
+                },
+            ]
+        );
+        assert_eq!(index.data_block_count(), 1);
+        assert_eq!(index.data_block_info(1), None);
+    }
+
+    #[test]
+    fn failed_segment_is_not_recorded() {
+        let mut index = Index::new();
+        let mut segment = data_only_segment();
+        segment.meta_data = None;
+        assert!(index.add_segment(segment).is_err());
+        assert!(index.segments().is_empty());
+        assert_eq!(
+            index.get_channel_data_positions(&ChannelPath::new("group", "ch1")),
+            None::<&[DataLocation]>
+        );
+    }
+
+    #[test]
+    fn channel_info_reports_type_change() {
+        let mut index = Index::new();
+        index.add_segment(first_segment()).unwrap();
+
+        let mut segment = first_segment();
+        segment.toc.contains_new_object_list = false;
+        segment.toc.big_endian = true;
+        let objects = &mut segment.meta_data.as_mut().unwrap().objects;
+        objects.truncate(2);
+        objects[1].raw_data_index = RawDataIndex::RawData(raw_meta(DataType::I64, 20));
+        objects[1].properties = vec![("unit".to_string(), PropertyValue::String("A".to_string()))];
+        index.add_segment(segment).unwrap();
+
+        let info = index
+            .channel_info(&ChannelPath::new("group", "ch1"))
+            .unwrap();
+        assert_eq!(info.data_type, Some(DataType::I64));
+        assert!(info.data_type_changed);
+        assert_eq!(info.length, 80);
+        assert_eq!(info.number_of_blocks, 2);
+        assert_eq!(info.layouts, vec![DataLayout::Contigious]);
+        assert_eq!(info.byte_orders, vec![Endianess::Little, Endianess::Big]);
+        assert_eq!(
+            info.properties.get("unit"),
+            Some(&PropertyValue::String("A".to_string()))
+        );
+
+        let info = index
+            .channel_info(&ChannelPath::new("group", "ch2"))
+            .unwrap();
+        assert_eq!(info.data_type, Some(DataType::I32));
+        assert!(!info.data_type_changed);
+
+        assert_eq!(index.channel_info(&ChannelPath::new("group", "ch3")), None);
+    }
+}
@@ -6,6 +6,9 @@
 //!
 //!
 mod building;
+mod inspection;
+mod property_history;
+mod property_query;
 mod querying;
 mod writing;
 
@@ -16,6 +19,11 @@ use crate::error::TdmsError;
 use crate::meta_data::{ObjectMetaData, RawDataIndex, RawDataMeta};
 use crate::paths::{ChannelPath, PropertyPath};
 use crate::raw_data::DataBlock;
+pub(crate) use inspection::SegmentRecord;
+pub use inspection::{BlockChannel, ChannelInfo, DataBlockInfo, SegmentInfo, SegmentObject};
+pub use property_history::PropertyChange;
+use property_history::PropertyHistory;
+pub use property_query::{Comparison, PropertyPredicate};
 
 /// A store for a given channel point to the data block with its data and the index within that.
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -118,6 +126,8 @@ pub struct Index {
     active_objects: Vec<building::ActiveObject>,
     objects: ObjectIndex,
     data_blocks: Vec<DataBlock>,
+    segments: Vec<SegmentRecord>,
+    property_history: Option<PropertyHistory>,
     next_segment_start: u64,
 }
 
@@ -126,6 +136,11 @@ impl Index {
         Self::default()
     }
 
+    /// The length of the file covered by the indexed segments.
+    pub(crate) fn indexed_length(&self) -> u64 {
+        self.next_segment_start
+    }
+
     /// Get all of the properties for the given object.
     ///
     /// Returns none if the object does not exist.
@@ -179,6 +194,11 @@ impl Index {
     pub fn get_data_block(&self, index: usize) -> Option<&DataBlock> {
         self.data_blocks.get(index)
     }
+
+    /// Get the most recently added data block.
+    pub(crate) fn last_data_block(&self) -> Option<&DataBlock> {
+        self.data_blocks.last()
+    }
 }
 
 #[cfg(test)]
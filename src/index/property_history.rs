@@ -0,0 +1,173 @@
+//! Optional tracking of every value written to each property.
+//!
+//! The index normally keeps only the latest value of a property. When history is
+//! enabled each definition is also recorded with the segment it was written in.
+
+use std::collections::BTreeMap;
+
+use crate::PropertyValue;
+use crate::meta_data::Segment;
+use crate::paths::PropertyPath;
+
+/// A value written to a property.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PropertyChange {
+    /// The index of the segment containing the value, matching [`crate::TdmsFile::segments`].
+    pub segment: usize,
+    pub value: PropertyValue,
+}
+
+/// The values for each property keyed by object path and then property name.
+pub(super) type PropertyHistory = BTreeMap<String, BTreeMap<String, Vec<PropertyChange>>>;
+
+impl super::Index {
+    /// Create an empty index which records the history of every property.
+    pub(crate) fn with_property_history() -> Self {
+        Self {
+            property_history: Some(PropertyHistory::new()),
+            ..Self::default()
+        }
+    }
+
+    /// True if the index is recording the property history.
+    pub(crate) fn has_property_history(&self) -> bool {
+        self.property_history.is_some()
+    }
+
+    /// Get every value written to the property in file order.
+    ///
+    /// Returns `None` if history isn't enabled or the property has never been written.
+    pub(crate) fn get_property_history(
+        &self,
+        path: &PropertyPath,
+        property: &str,
+    ) -> Option<&[PropertyChange]> {
+        self.property_history
+            .as_ref()?
+            .get(path.path())?
+            .get(property)
+            .map(|changes| &changes[..])
+    }
+
+    /// Record the properties from the segment which will be stored at `segment_index`.
+    pub(super) fn record_property_history(&mut self, segment: &Segment, segment_index: usize) {
+        let (Some(history), Some(meta_data)) = (&mut self.property_history, &segment.meta_data)
+        else {
+            return;
+        };
+
+        for object in meta_data.objects.iter() {
+            if object.properties.is_empty() {
+                continue;
+            }
+            let object_history = history.entry(object.path.clone()).or_default();
+            for (name, value) in object.properties.iter() {
+                object_history
+                    .entry(name.clone())
+                    .or_default()
+                    .push(PropertyChange {
+                        segment: segment_index,
+                        value: value.clone(),
+                    });
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::index::Index;
+    use crate::meta_data::{MetaData, ObjectMetaData, RawDataIndex, ToC};
+
+    fn property_segment(path: &str, properties: Vec<(&str, PropertyValue)>) -> Segment {
+        Segment {
+            toc: ToC {
+                contains_meta_data: true,
+                ..Default::default()
+            },
+            next_segment_offset: 100,
+            raw_data_offset: 100,
+            meta_data: Some(MetaData {
+                objects: vec![ObjectMetaData {
+                    path: path.to_string(),
+                    properties: properties
+                        .into_iter()
+                        .map(|(name, value)| (name.to_string(), value))
+                        .collect(),
+                    raw_data_index: RawDataIndex::None,
+                }],
+            }),
+        }
+    }
+
+    fn status(value: &str) -> PropertyValue {
+        PropertyValue::String(value.to_string())
+    }
+
+    #[test]
+    fn records_each_value_with_segment() {
+        let mut index = Index::with_property_history();
+        index
+            .add_segment(property_segment(
+                "/'group'",
+                vec![
+                    ("status", status("idle")),
+                    ("setpoint", PropertyValue::I32(1)),
+                ],
+            ))
+            .unwrap();
+        index
+            .add_segment(property_segment("/'other'", vec![("status", status("x"))]))
+            .unwrap();
+        index
+            .add_segment(property_segment(
+                "/'group'",
+                vec![("status", status("running"))],
+            ))
+            .unwrap();
+
+        let group = PropertyPath::group("group");
+        assert_eq!(
+            index.get_property_history(&group, "status").unwrap(),
+            &[
+                PropertyChange {
+                    segment: 0,
+                    value: status("idle"),
+                },
+                PropertyChange {
+                    segment: 2,
+                    value: status("running"),
+                },
+            ]
+        );
+        assert_eq!(
+            index
+                .get_property_history(&group, "setpoint")
+                .unwrap()
+                .len(),
+            1
+        );
+        assert_eq!(index.get_property_history(&group, "missing"), None);
+        assert_eq!(
+            index.get_object_property(&group, "status").unwrap(),
+            Some(&status("running"))
+        );
+    }
+
+    #[test]
+    fn history_is_off_by_default() {
+        let mut index = Index::new();
+        index
+            .add_segment(property_segment(
+                "/'group'",
+                vec![("status", status("idle"))],
+            ))
+            .unwrap();
+        assert!(!index.has_property_history());
+        assert_eq!(
+            index.get_property_history(&PropertyPath::group("group"), "status"),
+            None
+        );
+    }
+}
@@ -0,0 +1,241 @@
+//! Predicates for finding objects by their properties.
+
+use std::collections::BTreeMap;
+
+use crate::PropertyValue;
+
+/// A comparison used by [`PropertyPredicate::Compare`].
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Comparison {
+    LessThan,
+    LessOrEqual,
+    GreaterThan,
+    GreaterOrEqual,
+}
+
+/// A test against a single property of an object.
+///
+/// Objects without the named property never match.
+#[derive(Clone, PartialEq, Debug)]
+pub enum PropertyPredicate {
+    /// The property exists with any value.
+    Exists { name: String },
+    /// The property has exactly this value, including the type.
+    Equals { name: String, value: PropertyValue },
+    /// The property is a string matching the glob pattern.
+    ///
+    /// `*` matches any sequence of characters and `?` matches a single character.
+    Glob { name: String, pattern: String },
+    /// The property is numeric and compares with the value.
+    ///
+    /// Integer and float properties are converted to `f64` for the comparison.
+    Compare {
+        name: String,
+        comparison: Comparison,
+        value: f64,
+    },
+}
+
+impl PropertyPredicate {
+    /// Match objects which have the property.
+    pub fn exists(name: &str) -> Self {
+        Self::Exists {
+            name: name.to_string(),
+        }
+    }
+
+    /// Match objects where the property is equal to the value.
+    pub fn equals(name: &str, value: PropertyValue) -> Self {
+        Self::Equals {
+            name: name.to_string(),
+            value,
+        }
+    }
+
+    /// Match objects where the property is a string matching the glob pattern.
+    pub fn glob(name: &str, pattern: &str) -> Self {
+        Self::Glob {
+            name: name.to_string(),
+            pattern: pattern.to_string(),
+        }
+    }
+
+    /// Match objects where the property is a number which compares with the value.
+    pub fn compare(name: &str, comparison: Comparison, value: f64) -> Self {
+        Self::Compare {
+            name: name.to_string(),
+            comparison,
+            value,
+        }
+    }
+
+    fn name(&self) -> &str {
+        match self {
+            Self::Exists { name }
+            | Self::Equals { name, .. }
+            | Self::Glob { name, .. }
+            | Self::Compare { name, .. } => name,
+        }
+    }
+
+    /// Test the predicate against the properties of an object.
+    pub(super) fn matches(&self, properties: &BTreeMap<String, PropertyValue>) -> bool {
+        let Some(property) = properties.get(self.name()) else {
+            return false;
+        };
+
+        match self {
+            Self::Exists { .. } => true,
+            Self::Equals { value, .. } => property == value,
+            Self::Glob { pattern, .. } => match property {
+                PropertyValue::String(text) => glob_match(pattern, text),
+                _ => false,
+            },
+            Self::Compare {
+                comparison, value, ..
+            } => match numeric_value(property) {
+                Some(property) => match comparison {
+                    Comparison::LessThan => property < *value,
+                    Comparison::LessOrEqual => property <= *value,
+                    Comparison::GreaterThan => property > *value,
+                    Comparison::GreaterOrEqual => property >= *value,
+                },
+                None => false,
+            },
+        }
+    }
+}
+
+fn numeric_value(value: &PropertyValue) -> Option<f64> {
+    match value {
+        PropertyValue::I8(value) => Some(*value as f64),
+        PropertyValue::I16(value) => Some(*value as f64),
+        PropertyValue::I32(value) => Some(*value as f64),
+        PropertyValue::I64(value) => Some(*value as f64),
+        PropertyValue::U8(value) => Some(*value as f64),
+        PropertyValue::U16(value) => Some(*value as f64),
+        PropertyValue::U32(value) => Some(*value as f64),
+        PropertyValue::U64(value) => Some(*value as f64),
+        PropertyValue::SingleFloat(value) => Some(*value as f64),
+        PropertyValue::DoubleFloat(value) => Some(*value),
+        _ => None,
+    }
+}
+
+/// Match the text against a glob pattern supporting `*` and `?`.
+fn glob_match(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let text: Vec<char> = text.chars().collect();
+
+    let mut p = 0;
+    let mut t = 0;
+    // The position of the last star and the text position it was matched from,
+    // so we can backtrack and let the star consume another character.
+    let mut star: Option<(usize, usize)> = None;
+
+    while t < text.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                star = Some((p, t));
+                p += 1;
+            }
+            Some('?') => {
+                p += 1;
+                t += 1;
+            }
+            Some(c) if *c == text[t] => {
+                p += 1;
+                t += 1;
+            }
+            _ => match star {
+                Some((star_p, star_t)) => {
+                    p = star_p + 1;
+                    t = star_t + 1;
+                    star = Some((star_p, star_t + 1));
+                }
+                None => return false,
+            },
+        }
+    }
+
+    pattern[p..].iter().all(|c| *c == '*')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn properties() -> BTreeMap<String, PropertyValue> {
+        BTreeMap::from([
+            (
+                "unit_string".to_string(),
+                PropertyValue::String("V".to_string()),
+            ),
+            (
+                "DUT_Serial".to_string(),
+                PropertyValue::String("SN-1234-A".to_string()),
+            ),
+            (
+                "NI_ChannelLength".to_string(),
+                PropertyValue::U64(2_000_000),
+            ),
+            ("gain".to_string(), PropertyValue::DoubleFloat(0.5)),
+        ])
+    }
+
+    #[test]
+    fn test_glob_match() {
+        assert!(glob_match("SN-*", "SN-1234-A"));
+        assert!(glob_match("*-A", "SN-1234-A"));
+        assert!(glob_match("SN-????-?", "SN-1234-A"));
+        assert!(glob_match("*12*4*", "SN-1234-A"));
+        assert!(glob_match("*", ""));
+        assert!(!glob_match("SN-???-A", "SN-1234-A"));
+        assert!(!glob_match("sn-*", "SN-1234-A"));
+        assert!(!glob_match("", "a"));
+    }
+
+    #[test]
+    fn test_equals_requires_matching_type() {
+        let properties = properties();
+        assert!(
+            PropertyPredicate::equals("unit_string", PropertyValue::String("V".to_string()))
+                .matches(&properties)
+        );
+        assert!(
+            !PropertyPredicate::equals("unit_string", PropertyValue::String("A".to_string()))
+                .matches(&properties)
+        );
+        assert!(
+            !PropertyPredicate::equals("NI_ChannelLength", PropertyValue::I32(2_000_000))
+                .matches(&properties)
+        );
+    }
+
+    #[test]
+    fn test_compare_converts_numbers() {
+        let properties = properties();
+        assert!(
+            PropertyPredicate::compare("NI_ChannelLength", Comparison::GreaterThan, 1e6)
+                .matches(&properties)
+        );
+        assert!(
+            PropertyPredicate::compare("gain", Comparison::LessOrEqual, 0.5).matches(&properties)
+        );
+        assert!(
+            !PropertyPredicate::compare("gain", Comparison::LessThan, 0.5).matches(&properties)
+        );
+        assert!(
+            !PropertyPredicate::compare("unit_string", Comparison::GreaterThan, 0.0)
+                .matches(&properties)
+        );
+    }
+
+    #[test]
+    fn test_missing_property_never_matches() {
+        let properties = properties();
+        assert!(PropertyPredicate::exists("gain").matches(&properties));
+        assert!(!PropertyPredicate::exists("offset").matches(&properties));
+        assert!(!PropertyPredicate::glob("offset", "*").matches(&properties));
+    }
+}
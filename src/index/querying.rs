@@ -6,8 +6,8 @@
 
 use std::ops::Bound;
 
-use super::Index;
-use crate::paths::ObjectPath;
+use super::{Index, PropertyPredicate};
+use crate::paths::{ObjectPath, PropertyPath};
 
 /// Implement methods for getting the various objects from the index.
 ///
@@ -35,6 +35,30 @@ impl Index {
             .map(|(path, _)| path.as_str())
             .take_while(move |p| p.starts_with(path))
     }
+
+    /// Get the paths of the objects whose properties match all of the predicates.
+    ///
+    /// Paths which can't be parsed are skipped as they can't be used with the rest of the API.
+    pub(crate) fn objects_matching<'a>(
+        &'a self,
+        predicates: &'a [PropertyPredicate],
+    ) -> impl Iterator<Item = PropertyPath> + 'a {
+        self.objects
+            .values()
+            .filter(|object| {
+                predicates
+                    .iter()
+                    .all(|predicate| predicate.matches(&object.properties))
+            })
+            .filter_map(|object| PropertyPath::try_from(object.path.as_str()).ok())
+    }
+
+    /// Get the paths of the objects expected in the next data block, in the order they are stored.
+    pub(crate) fn active_paths(&self) -> impl Iterator<Item = ObjectPath<'_>> {
+        self.active_objects
+            .iter()
+            .map(|object| object.path.as_str())
+    }
 }
 
 #[cfg(test)]
@@ -160,4 +184,29 @@ mod tests {
             .collect();
         assert!(paths.is_empty());
     }
+
+    #[test]
+    fn test_objects_matching() {
+        let index = generate_test_index();
+        let predicates = [PropertyPredicate::equals("Prop1", PropertyValue::I32(-1))];
+        let paths: Vec<_> = index.objects_matching(&predicates).collect();
+        assert_eq!(
+            paths,
+            vec![
+                PropertyPath::channel("group", "ch1"),
+                PropertyPath::channel("group2", "ch1"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_objects_matching_requires_all_predicates() {
+        let index = generate_test_index();
+        let predicates = [
+            PropertyPredicate::exists("Prop"),
+            PropertyPredicate::exists("Prop1"),
+        ];
+        assert_eq!(index.objects_matching(&predicates).count(), 0);
+        assert_eq!(index.objects_matching(&[]).count(), 7);
+    }
 }
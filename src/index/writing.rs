@@ -14,17 +14,18 @@ impl Index {
         &self,
         objects: Vec<(&'b str, DataFormat)>,
     ) -> (bool, Vec<(&'b str, RawDataIndex)>) {
-        let live_matches = if !self.active_objects.is_empty() {
-            self.active_objects
-                .iter()
-                .zip(objects.iter())
-                .fold(true, |matches, (active, new)| {
-                    matches && active.path == new.0
-                })
-        } else {
-            //empty
-            false
-        };
+        let live_matches =
+            if !self.active_objects.is_empty() && self.active_objects.len() == objects.len() {
+                self.active_objects
+                    .iter()
+                    .zip(objects.iter())
+                    .fold(true, |matches, (active, new)| {
+                        matches && active.path == new.0
+                    })
+            } else {
+                //empty
+                false
+            };
 
         let raw_data_formats = objects
             .into_iter()
@@ -413,6 +414,55 @@ mod tests {
         assert_eq!(data_format, expected_format);
     }
 
+    #[test]
+    fn matches_live_no_match_subset_of_channels() {
+        let segment = Segment {
+            toc: ToC::from_u32(0xE),
+            next_segment_offset: 500,
+            raw_data_offset: 20,
+            meta_data: Some(MetaData {
+                objects: vec![
+                    ObjectMetaData {
+                        path: "/'group'/'ch1'".to_string(),
+                        properties: vec![],
+                        raw_data_index: RawDataIndex::RawData(RawDataMeta {
+                            data_type: DataType::DoubleFloat,
+                            number_of_values: 1000,
+                            total_size_bytes: None,
+                        }),
+                    },
+                    ObjectMetaData {
+                        path: "/'group'/'ch2'".to_string(),
+                        properties: vec![],
+                        raw_data_index: RawDataIndex::RawData(RawDataMeta {
+                            data_type: DataType::DoubleFloat,
+                            number_of_values: 1000,
+                            total_size_bytes: None,
+                        }),
+                    },
+                ],
+            }),
+        };
+
+        let mut index = Index::default();
+        index.add_segment(segment).unwrap();
+
+        let channels = vec![(
+            "/'group'/'ch1'",
+            DataFormat::RawData(RawDataMeta {
+                data_type: DataType::DoubleFloat,
+                number_of_values: 1000,
+                total_size_bytes: None,
+            }),
+        )];
+        let (matches, data_format) = index.check_write_values(channels);
+        assert!(!matches);
+        assert_eq!(
+            data_format,
+            vec![("/'group'/'ch1'", RawDataIndex::MatchPrevious)]
+        );
+    }
+
     #[test]
     fn uses_previous_data_format_even_with_no_match() {
         let segment = Segment {
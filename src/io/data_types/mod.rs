@@ -128,6 +128,25 @@ pub trait TdmsStorageType: Sized + 'static {
     const SIZE_BYTES: usize = std::mem::size_of::<Self>();
     fn read_le(reader: &mut impl Read) -> StorageResult<Self>;
     fn read_be(reader: &mut impl Read) -> StorageResult<Self>;
+    /// Read little endian values to fill the output.
+    ///
+    /// By default this reads each value in turn. Fixed size numeric types
+    /// override this to read the whole slice in one go.
+    fn read_le_slice(reader: &mut impl Read, output: &mut [Self]) -> StorageResult<()> {
+        for value in output {
+            *value = Self::read_le(reader)?;
+        }
+        Ok(())
+    }
+    /// Read big endian values to fill the output.
+    ///
+    /// See [`Self::read_le_slice`].
+    fn read_be_slice(reader: &mut impl Read, output: &mut [Self]) -> StorageResult<()> {
+        for value in output {
+            *value = Self::read_be(reader)?;
+        }
+        Ok(())
+    }
     /// Write the value as little endian.
     fn write_le(&self, writer: &mut impl Write) -> StorageResult<()>;
     /// Write the value as big endian.
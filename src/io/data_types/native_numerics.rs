@@ -5,6 +5,26 @@ use std::io::{Read, Write};
 
 use super::*;
 
+/// Marker for the primitive numeric types where any bytes are a valid value.
+///
+/// # Safety
+///
+/// Only implement this for types with no padding and no invalid bit patterns.
+unsafe trait PlainNumeric: Copy {}
+
+/// View the values as bytes so we can read straight into them.
+///
+/// The values will be in the byte order of the file until they are swapped.
+fn as_mut_bytes<T: PlainNumeric>(values: &mut [T]) -> &mut [u8] {
+    // SAFETY: `PlainNumeric` types have no padding and any bytes are valid values.
+    unsafe {
+        std::slice::from_raw_parts_mut(
+            values.as_mut_ptr() as *mut u8,
+            std::mem::size_of_val(values),
+        )
+    }
+}
+
 /// Macro for scripting the wrapping of the different read methods.
 ///
 /// Should provide the type which has a from_le_bytes and from_be_bytes
@@ -12,6 +32,9 @@ use super::*;
 /// and then a slice of supported [`DataType`] values.
 macro_rules! numeric_type {
     ($type:ty, $natural:expr, $supported:expr) => {
+        // SAFETY: Only used with primitive integers and floats.
+        unsafe impl PlainNumeric for $type {}
+
         impl TdmsStorageType for $type {
             const NATURAL_TYPE: DataType = $natural;
             const SUPPORTED_TYPES: &'static [DataType] = $supported;
@@ -25,6 +48,20 @@ macro_rules! numeric_type {
                 reader.read_exact(&mut buf)?;
                 Ok(<$type>::from_be_bytes(buf))
             }
+            fn read_le_slice(reader: &mut impl Read, output: &mut [$type]) -> StorageResult<()> {
+                reader.read_exact(as_mut_bytes(output))?;
+                for value in output.iter_mut() {
+                    *value = <$type>::from_le_bytes(value.to_ne_bytes());
+                }
+                Ok(())
+            }
+            fn read_be_slice(reader: &mut impl Read, output: &mut [$type]) -> StorageResult<()> {
+                reader.read_exact(as_mut_bytes(output))?;
+                for value in output.iter_mut() {
+                    *value = <$type>::from_be_bytes(value.to_ne_bytes());
+                }
+                Ok(())
+            }
             fn write_le(&self, writer: &mut impl Write) -> StorageResult<()> {
                 writer.write_all(&self.to_le_bytes())?;
                 Ok(())
@@ -103,6 +140,24 @@ mod tests {
                     }
                     assert_eq!(bytes, output_bytes);
                 }
+
+                #[test]
+                fn [< test_ $type _slices >] () {
+                    let original_value: $type = $test_value;
+                    let values = [original_value, 0 as $type, original_value];
+
+                    let le_bytes: Vec<u8> = values.iter().flat_map(|v| v.to_le_bytes()).collect();
+                    let mut tdms_reader = LittleEndianReader::from_reader(Cursor::new(le_bytes));
+                    let mut output = [1 as $type; 3];
+                    tdms_reader.read_values(&mut output[..]).unwrap();
+                    assert_eq!(output, values);
+
+                    let be_bytes: Vec<u8> = values.iter().flat_map(|v| v.to_be_bytes()).collect();
+                    let mut tdms_reader = BigEndianReader::from_reader(Cursor::new(be_bytes));
+                    let mut output = [1 as $type; 3];
+                    tdms_reader.read_values(&mut output[..]).unwrap();
+                    assert_eq!(output, values);
+                }
             }
         };
     }
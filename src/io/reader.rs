@@ -10,6 +10,8 @@ use super::data_types::TdmsStorageType;
 pub trait TdmsReader<R: Read + Seek>: Sized {
     fn from_reader(reader: R) -> Self;
     fn read_value<T: TdmsStorageType>(&mut self) -> Result<T, TdmsError>;
+    /// Read values to fill the output.
+    fn read_values<T: TdmsStorageType>(&mut self, output: &mut [T]) -> Result<(), TdmsError>;
     fn read_meta<T: TdmsMetaData>(&mut self) -> Result<T, TdmsError> {
         T::read(self)
     }
@@ -46,14 +48,17 @@ pub trait TdmsReader<R: Read + Seek>: Sized {
         let next_segment_offset = self.read_value()?;
         let raw_data_offset = self.read_value()?;
 
-        //todo handle no meta data mode.
-        let meta_data = self.read_meta()?;
+        let meta_data = if toc.contains_meta_data {
+            Some(self.read_meta()?)
+        } else {
+            None
+        };
 
         Ok(Segment {
             toc,
             next_segment_offset,
             raw_data_offset,
-            meta_data: Some(meta_data),
+            meta_data,
         })
     }
 }
@@ -65,6 +70,10 @@ impl<R: Read + Seek> TdmsReader<R> for LittleEndianReader<R> {
         T::read_le(&mut self.0)
     }
 
+    fn read_values<T: TdmsStorageType>(&mut self, output: &mut [T]) -> Result<(), TdmsError> {
+        T::read_le_slice(&mut self.0, output)
+    }
+
     fn from_reader(reader: R) -> Self {
         Self(BufReader::new(reader))
     }
@@ -81,6 +90,10 @@ impl<R: Read + Seek> TdmsReader<R> for BigEndianReader<R> {
         T::read_be(&mut self.0)
     }
 
+    fn read_values<T: TdmsStorageType>(&mut self, output: &mut [T]) -> Result<(), TdmsError> {
+        T::read_be_slice(&mut self.0, output)
+    }
+
     fn from_reader(reader: R) -> Self {
         Self(BufReader::new(reader))
     }
@@ -140,4 +153,17 @@ mod tests {
         let string: String = reader.read_value().unwrap();
         assert_eq!(string, String::from("/'Measured Throughput Data (Volts)'"));
     }
+
+    #[test]
+    fn test_segment_without_meta_data() {
+        let mut buffer = Vec::new();
+        buffer.extend_from_slice(&4713u32.to_le_bytes());
+        buffer.extend_from_slice(&16u64.to_le_bytes());
+        buffer.extend_from_slice(&16u64.to_le_bytes());
+        let mut cursor = Cursor::new(buffer);
+        let mut reader = LittleEndianReader::from_reader(&mut cursor);
+        let segment = reader.read_segment(ToC::from_u32(0x08)).unwrap();
+        assert!(segment.meta_data.is_none());
+        assert_eq!(segment.next_segment_offset, 16);
+    }
 }
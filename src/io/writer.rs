@@ -78,6 +78,14 @@ pub trait TdmsWriter<W: Write>: Sized {
     }
 
     fn sync(&mut self) -> Result<()>;
+
+    /// Flush any buffered data and return the underlying writer.
+    fn into_inner(self) -> Result<W>;
+
+    /// Get a reference to the underlying writer.
+    ///
+    /// This doesn't flush so buffered data may not have reached it yet.
+    fn get_ref(&self) -> &W;
 }
 
 pub struct LittleEndianWriter<W: Write>(BufWriter<W>);
@@ -96,6 +104,15 @@ impl<W: Write> TdmsWriter<W> for LittleEndianWriter<W> {
         self.0.flush()?;
         Ok(())
     }
+
+    fn into_inner(self) -> Result<W> {
+        let inner = self.0.into_inner().map_err(|e| e.into_error())?;
+        Ok(inner)
+    }
+
+    fn get_ref(&self) -> &W {
+        self.0.get_ref()
+    }
 }
 
 pub struct BigEndianWriter<W: Write>(BufWriter<W>);
@@ -114,6 +131,15 @@ impl<W: Write> TdmsWriter<W> for BigEndianWriter<W> {
         self.0.flush()?;
         Ok(())
     }
+
+    fn into_inner(self) -> Result<W> {
+        let inner = self.0.into_inner().map_err(|e| e.into_error())?;
+        Ok(inner)
+    }
+
+    fn get_ref(&self) -> &W {
+        self.0.get_ref()
+    }
 }
 
 #[cfg(test)]
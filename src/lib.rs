@@ -6,16 +6,40 @@ mod meta_data;
 mod paths;
 mod properties;
 mod raw_data;
+mod stream_decoder;
 
 // Re-exports.
 pub use error::TdmsError;
+pub use file::BlockCacheStats;
+pub use file::CopyFilter;
+#[cfg(any(unix, windows))]
+pub use file::FileStorage;
+#[cfg(feature = "mmap")]
+pub use file::MmapTdmsFile;
+pub use file::RawDataBlock;
+pub use file::ReadOnly;
+#[cfg(any(unix, windows))]
+pub use file::SharedTdmsFile;
 pub use file::TdmsFile;
 pub use file::TdmsFileWriter;
+pub use file::TdmsWriterHandle;
+#[cfg(feature = "tokio")]
+pub use file::{AsyncTdmsFile, AsyncTdmsFileWriter};
+pub use file::{BackgroundWriter, FlushHandle, WriteQueue};
+pub use file::{ChannelStructure, FileStructure, GroupStructure};
+pub use file::{MemoryStorage, StorageStream, StorageTdmsFile, TdmsStorage};
+pub use file::{SyncPolicy, SyncToDisk};
+pub use index::{
+    BlockChannel, ChannelInfo, DataBlockInfo, DataLocation, SegmentInfo, SegmentObject,
+};
+pub use index::{Comparison, PropertyChange, PropertyPredicate};
 pub use io::data_types::DataType;
 pub use io::data_types::TdmsStorageType;
+pub use meta_data::{RawDataIndex, RawDataMeta, ToC};
 pub use paths::{ChannelPath, PropertyPath};
 pub use properties::PropertyValue;
-pub use raw_data::DataLayout;
+pub use raw_data::{DataLayout, Endianess};
+pub use stream_decoder::{DecodeEvent, SampleBatch, StreamDecoder};
 
 // Put the types in their own namespace.
 pub mod types {
@@ -46,6 +46,7 @@ impl TdmsMetaData for DataType {
 
 /// An extracted form of a segment table of contents.
 #[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+#[non_exhaustive]
 pub struct ToC {
     pub contains_meta_data: bool,
     pub contains_raw_data: bool,
@@ -173,6 +174,31 @@ impl Segment {
         }
     }
 
+    /// The length of the lead in and metadata for the segment starting with `lead_in`.
+    ///
+    /// Readers which load the file in ranges can use this to load the whole header
+    /// before parsing it with [`Segment::read`].
+    pub fn header_length(lead_in: &[u8; LEAD_IN_BYTES as usize]) -> Result<u64, TdmsError> {
+        let tag: [u8; 4] = lead_in[0..4].try_into().unwrap();
+        if tag != [0x54, 0x44, 0x53, 0x6D] {
+            return Err(TdmsError::HeaderPatternNotMatched(tag));
+        }
+
+        //ToC is always little endian.
+        let toc = ToC::from_u32(u32::from_le_bytes(lead_in[4..8].try_into().unwrap()));
+        if !toc.contains_meta_data {
+            return Ok(LEAD_IN_BYTES);
+        }
+        let raw_data_offset: [u8; 8] = lead_in[20..28].try_into().unwrap();
+        let meta_data_length = match toc.big_endian {
+            true => u64::from_be_bytes(raw_data_offset),
+            false => u64::from_le_bytes(raw_data_offset),
+        };
+        meta_data_length
+            .checked_add(LEAD_IN_BYTES)
+            .ok_or(TdmsError::SegmentAddressOverflow)
+    }
+
     pub fn read(reader: &mut (impl Read + Seek)) -> Result<Segment, TdmsError> {
         let mut tag = [0u8; 4];
         match reader.read_exact(&mut tag) {
@@ -266,7 +292,9 @@ impl TdmsMetaData for ObjectMetaData {
     }
 }
 
+/// The raw data index of an object in a segment.
 #[derive(Debug, PartialEq, Eq, Clone)]
+#[non_exhaustive]
 pub enum RawDataIndex {
     None,
     MatchPrevious,
@@ -286,10 +314,15 @@ impl TdmsMetaData for RawDataIndex {
                 let data_type: DataType = reader.read_meta()?;
                 let _array_dims: u32 = reader.read_value()?; //always 1.
                 let number_of_values: u64 = reader.read_value()?;
+                // Variable size data also records the total size in bytes.
+                let total_size_bytes = match data_type {
+                    DataType::TdmsString => Some(reader.read_value()?),
+                    _ => None,
+                };
                 let meta = RawDataMeta {
                     data_type,
                     number_of_values,
-                    total_size_bytes: None,
+                    total_size_bytes,
                 };
                 RawDataIndex::RawData(meta)
             }
@@ -303,12 +336,14 @@ impl TdmsMetaData for RawDataIndex {
             RawDataIndex::None => writer.write_value(&0xFFFF_FFFFu32)?,
             RawDataIndex::MatchPrevious => writer.write_value(&0u32)?,
             RawDataIndex::RawData(raw_meta) => {
-                //size: until we add string support it is 20 bytes.
-                writer.write_value(&20u32)?;
+                writer.write_value(&(self.size() as u32))?;
                 writer.write_meta(&raw_meta.data_type)?;
                 //array dim is alway 1 in TDMS v2.0.
                 writer.write_value(&1u32)?;
-                writer.write_value(&raw_meta.number_of_values)?
+                writer.write_value(&raw_meta.number_of_values)?;
+                if let Some(total_size_bytes) = raw_meta.total_size_bytes {
+                    writer.write_value(&total_size_bytes)?;
+                }
             }
         }
         Ok(())
@@ -318,14 +353,20 @@ impl TdmsMetaData for RawDataIndex {
         match self {
             RawDataIndex::None => std::mem::size_of::<u32>(),
             RawDataIndex::MatchPrevious => std::mem::size_of::<u32>(),
-            RawDataIndex::RawData(_raw_meta) => {
-                3 * std::mem::size_of::<u32>() + std::mem::size_of::<u64>()
+            RawDataIndex::RawData(raw_meta) => {
+                let total_size = match raw_meta.total_size_bytes {
+                    Some(_) => std::mem::size_of::<u64>(),
+                    None => 0,
+                };
+                3 * std::mem::size_of::<u32>() + std::mem::size_of::<u64>() + total_size
             }
         }
     }
 }
 
+/// The layout of a channel's raw data in a segment.
 #[derive(Debug, PartialEq, Eq, Clone)]
+#[non_exhaustive]
 pub struct RawDataMeta {
     pub data_type: DataType,
     pub number_of_values: u64,
@@ -617,4 +658,26 @@ mod tests {
         let output = write_meta_to_buffer(meta, expected_buffer.len());
         assert_eq!(output, expected_buffer);
     }
+
+    #[test]
+    fn test_string_raw_data_index_round_trip() {
+        // A string channel with 2 values and 13 bytes of string data.
+        let expected_buffer = [
+            0x1C, 0x00, 0x00, 0x00, 0x20, 0x00, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00, 0x02, 0x00,
+            0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x0D, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+        ];
+        let index = RawDataIndex::RawData(RawDataMeta {
+            data_type: DataType::TdmsString,
+            number_of_values: 2,
+            total_size_bytes: Some(13),
+        });
+
+        let output = write_meta_to_buffer(index.clone(), expected_buffer.len());
+        assert_eq!(output, expected_buffer);
+
+        let mut cursor = Cursor::new(expected_buffer);
+        let mut reader = LittleEndianReader::from_reader(&mut cursor);
+        let read: RawDataIndex = reader.read_meta().unwrap();
+        assert_eq!(read, index);
+    }
 }
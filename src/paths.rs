@@ -62,6 +62,38 @@ pub fn path_group_name(path: ObjectPath<'_>) -> Option<&str> {
     parse_path(path).ok()?.0
 }
 
+/// Get the new path for the object at `path` when renaming `from` to `to`.
+///
+/// Renaming a group also moves its channels. Returns `None` if the path isn't affected.
+/// The names are compared and reused in their escaped form so they are never escaped twice.
+pub(crate) fn renamed_path(
+    path: ObjectPath<'_>,
+    from: &PropertyPath,
+    to: &PropertyPath,
+) -> Option<PropertyPath> {
+    let (Some(group), channel) = parse_path(path).ok()? else {
+        return None;
+    };
+    let (Some(from_group), from_channel) = parse_path(from.path()).ok()? else {
+        return None;
+    };
+    let (Some(to_group), to_channel) = parse_path(to.path()).ok()? else {
+        return None;
+    };
+
+    if group != from_group {
+        return None;
+    }
+    match (channel, from_channel, to_channel) {
+        // Group rename moves the group and its channels.
+        (None, None, None) => Some(to.clone()),
+        (Some(channel), None, None) => Some(PropertyPath(format!("/'{to_group}'/'{channel}'"))),
+        // Channel rename only matches the channel itself.
+        (Some(channel), Some(from_channel), Some(_)) if channel == from_channel => Some(to.clone()),
+        _ => None,
+    }
+}
+
 fn invert<T, E>(x: Option<Result<T, E>>) -> Result<Option<T>, E> {
     x.map_or(Ok(None), |v| v.map(Some))
 }
@@ -392,4 +424,47 @@ mod tests {
         assert_eq!(path_group_name("/"), None);
         assert_eq!(path_group_name("invalid"), None);
     }
+
+    #[test]
+    fn test_renamed_path_group_moves_channels() {
+        let from = PropertyPath::group("group");
+        let to = PropertyPath::group("new");
+        assert_eq!(
+            renamed_path("/'group'", &from, &to),
+            Some(PropertyPath::group("new"))
+        );
+        assert_eq!(
+            renamed_path("/'group'/'channel'", &from, &to),
+            Some(PropertyPath::channel("new", "channel"))
+        );
+        assert_eq!(renamed_path("/'group2'/'channel'", &from, &to), None);
+        assert_eq!(renamed_path("/", &from, &to), None);
+    }
+
+    #[test]
+    fn test_renamed_path_channel() {
+        let from = PropertyPath::channel("group", "channel");
+        let to = PropertyPath::channel("other", "renamed");
+        assert_eq!(
+            renamed_path("/'group'/'channel'", &from, &to),
+            Some(PropertyPath::channel("other", "renamed"))
+        );
+        assert_eq!(renamed_path("/'group'", &from, &to), None);
+        assert_eq!(renamed_path("/'group'/'channel2'", &from, &to), None);
+    }
+
+    #[test]
+    fn test_renamed_path_keeps_escaped_names() {
+        let from = PropertyPath::group("rig'1");
+        let to = PropertyPath::group("rig'2");
+        let channel = PropertyPath::channel("rig'1", "it's");
+        assert_eq!(
+            renamed_path(channel.path(), &from, &to),
+            Some(PropertyPath::channel("rig'2", "it's"))
+        );
+        assert_eq!(
+            renamed_path(channel.path(), &from, &to).unwrap().path(),
+            r#"/'rig"2'/'it"s'"#
+        );
+    }
 }
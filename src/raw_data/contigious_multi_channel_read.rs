@@ -224,16 +224,19 @@ impl<R: Read + Seek, T: TdmsReader<R>> MultiChannelContiguousReader<R, T> {
     }
 
     /// Reads the samples until the specified value or the output ends.
-    fn read_sequential_samples<'a, D: TdmsStorageType, I: Iterator<Item = &'a mut D>>(
+    ///
+    /// The samples are read into the output in one go rather than value by value.
+    fn read_sequential_samples<'a, D: TdmsStorageType>(
         &mut self,
-        output: &mut I,
+        output: &mut std::slice::IterMut<'a, D>,
         samples_to_read: usize,
     ) -> Result<usize, TdmsError> {
-        let mut length = 0;
-        for output_value in output.take(samples_to_read) {
-            *output_value = self.reader.read_value()?;
-            length += 1;
-        }
+        let remaining_output = std::mem::take(output).into_slice();
+        let length = samples_to_read.min(remaining_output.len());
+        let (read_output, unused_output) = remaining_output.split_at_mut(length);
+        self.reader.read_values(read_output)?;
+        *output = unused_output.iter_mut();
+
         // Skip to end of unread samples.
         let unread_samples = samples_to_read - length;
         if unread_samples > 0 {
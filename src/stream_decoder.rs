@@ -0,0 +1,784 @@
+//! A push based decoder for TDMS data arriving from a stream.
+//!
+//! The file API needs [`std::io::Seek`] to jump between segments. This module
+//! instead accepts the bytes in order as they arrive (e.g. from a socket) and
+//! emits [`DecodeEvent`]s as soon as enough data is available.
+//!
+//! Internally this uses the same [`Index`] as the file API to track the active
+//! channels between segments.
+
+use std::collections::VecDeque;
+use std::io::Cursor;
+
+use crate::PropertyValue;
+use crate::error::TdmsError;
+use crate::index::Index;
+use crate::io::data_types::{DataType, TdmsStorageType};
+use crate::io::reader::{BigEndianReader, LittleEndianReader, TdmsReader};
+use crate::meta_data::{LEAD_IN_BYTES, MetaData, RawDataIndex, Segment, ToC};
+use crate::paths::{ChannelPath, PropertyPath};
+use crate::raw_data::{DataBlock, DataLayout, Endianess};
+
+const SEGMENT_TAG: [u8; 4] = [0x54, 0x44, 0x53, 0x6D];
+
+/// An event produced by the [`StreamDecoder`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum DecodeEvent {
+    /// The lead in for a new segment has been read.
+    SegmentStart {
+        /// The position of the segment in the stream.
+        offset: u64,
+        /// The table of contents for the segment.
+        toc: ToC,
+        /// The length of the segment excluding the lead in.
+        next_segment_offset: u64,
+        /// The length of the metadata in the segment.
+        raw_data_offset: u64,
+    },
+    /// An object was listed in the segment metadata.
+    Object {
+        path: PropertyPath,
+        /// True if the object has raw data in this segment.
+        has_data: bool,
+    },
+    /// A property was set on an object.
+    Property {
+        path: PropertyPath,
+        name: String,
+        value: PropertyValue,
+    },
+    /// Samples for a single channel.
+    Samples(SampleBatch),
+}
+
+/// A batch of samples for a single channel from a data chunk.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SampleBatch {
+    channel: ChannelPath,
+    data_type: DataType,
+    byte_order: Endianess,
+    bytes: Vec<u8>,
+}
+
+impl SampleBatch {
+    /// The channel these samples belong to.
+    pub fn channel(&self) -> &ChannelPath {
+        &self.channel
+    }
+
+    /// The data type of the samples as stored in the stream.
+    pub fn data_type(&self) -> DataType {
+        self.data_type
+    }
+
+    /// The number of samples in the batch.
+    pub fn len(&self) -> usize {
+        match self.data_type.size() {
+            0 => 0,
+            size => self.bytes.len() / size as usize,
+        }
+    }
+
+    /// True if there are no samples in the batch.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Decode the samples as the given type.
+    ///
+    /// Errors if the type is not compatible with the channel data type.
+    pub fn values<D: TdmsStorageType>(&self) -> Result<Vec<D>, TdmsError> {
+        if !D::supports_data_type(&self.data_type) {
+            return Err(TdmsError::DataTypeMismatch(self.data_type, D::NATURAL_TYPE));
+        }
+
+        let mut reader = &self.bytes[..];
+        let mut values = Vec::with_capacity(self.len());
+        for _ in 0..self.len() {
+            let value = match self.byte_order {
+                Endianess::Little => D::read_le(&mut reader)?,
+                Endianess::Big => D::read_be(&mut reader)?,
+            };
+            values.push(value);
+        }
+        Ok(values)
+    }
+}
+
+/// A channel in a data chunk.
+#[derive(Debug)]
+struct ChunkChannel {
+    path: ChannelPath,
+    data_type: DataType,
+    number_of_values: usize,
+    /// The bytes used by the channel in each chunk.
+    size: usize,
+    /// Variable size data such as strings is skipped rather than decoded.
+    variable_size: bool,
+}
+
+/// The layout of a chunk in a data block.
+#[derive(Debug)]
+struct ChunkFormat {
+    channels: Vec<ChunkChannel>,
+    layout: DataLayout,
+    byte_order: Endianess,
+    size: u64,
+}
+
+impl ChunkFormat {
+    fn new(block: &DataBlock, paths: Vec<ChannelPath>) -> Result<Self, TdmsError> {
+        let mut channels = Vec::with_capacity(block.channels.len());
+        let mut size: u64 = 0;
+        for (path, meta) in paths.into_iter().zip(block.channels.iter()) {
+            let channel_size = match meta.total_size_bytes {
+                Some(total_size) => total_size,
+                None => meta
+                    .number_of_values
+                    .checked_mul(meta.data_type.size() as u64)
+                    .ok_or(TdmsError::ChunkSizeOverflow)?,
+            };
+            size = size
+                .checked_add(channel_size)
+                .ok_or(TdmsError::ChunkSizeOverflow)?;
+            channels.push(ChunkChannel {
+                path,
+                data_type: meta.data_type,
+                number_of_values: usize::try_from(meta.number_of_values)
+                    .map_err(|_| TdmsError::ChunkSizeOverflow)?,
+                size: usize::try_from(channel_size).map_err(|_| TdmsError::ChunkSizeOverflow)?,
+                variable_size: meta.total_size_bytes.is_some(),
+            });
+        }
+
+        // Each interleaved row holds one value from every channel.
+        if block.layout == DataLayout::Interleaved
+            && let Some(first) = channels.first()
+            && channels
+                .iter()
+                .any(|channel| channel.number_of_values != first.number_of_values)
+        {
+            return Err(TdmsError::InterleavedLengthMismatch);
+        }
+
+        Ok(Self {
+            channels,
+            layout: block.layout,
+            byte_order: block.byte_order,
+            size,
+        })
+    }
+
+    /// True if there are any channels we can decode from the chunk.
+    ///
+    /// We can't find the values in interleaved chunks with variable size data so
+    /// those are skipped completely.
+    fn has_samples(&self) -> bool {
+        let any_variable_size = self.channels.iter().any(|channel| channel.variable_size);
+        let any_fixed_size = self.channels.iter().any(|channel| !channel.variable_size);
+        match self.layout {
+            DataLayout::Contigious => any_fixed_size,
+            DataLayout::Interleaved => any_fixed_size && !any_variable_size,
+        }
+    }
+
+    /// Split a chunk into the samples for each fixed size channel.
+    fn split(&self, chunk: &[u8]) -> Result<Vec<SampleBatch>, TdmsError> {
+        let mut outputs: Vec<Vec<u8>> = self
+            .channels
+            .iter()
+            .map(|channel| match channel.variable_size {
+                true => Vec::new(),
+                false => Vec::with_capacity(channel.size),
+            })
+            .collect();
+
+        match self.layout {
+            DataLayout::Contigious => {
+                let mut position = 0;
+                for (channel, output) in self.channels.iter().zip(outputs.iter_mut()) {
+                    if !channel.variable_size {
+                        output.extend_from_slice(chunk_bytes(chunk, position, channel.size)?);
+                    }
+                    position += channel.size;
+                }
+            }
+            DataLayout::Interleaved => {
+                // All channels have the same number of values, checked in new.
+                let rows = self
+                    .channels
+                    .first()
+                    .map_or(0, |channel| channel.number_of_values);
+                let mut position = 0;
+                for _ in 0..rows {
+                    for (channel, output) in self.channels.iter().zip(outputs.iter_mut()) {
+                        let size = channel.data_type.size() as usize;
+                        output.extend_from_slice(chunk_bytes(chunk, position, size)?);
+                        position += size;
+                    }
+                }
+            }
+        }
+
+        Ok(self
+            .channels
+            .iter()
+            .zip(outputs)
+            .filter(|(channel, _)| !channel.variable_size)
+            .map(|(channel, bytes)| SampleBatch {
+                channel: channel.path.clone(),
+                data_type: channel.data_type,
+                byte_order: self.byte_order,
+                bytes,
+            })
+            .collect())
+    }
+}
+
+/// Get bytes from the chunk, erroring if they run past the end.
+fn chunk_bytes(chunk: &[u8], position: usize, length: usize) -> Result<&[u8], TdmsError> {
+    position
+        .checked_add(length)
+        .and_then(|end| chunk.get(position..end))
+        .ok_or(TdmsError::EndOfFile)
+}
+
+#[derive(Debug)]
+enum DecoderState {
+    /// Waiting for the next segment lead in.
+    LeadIn,
+    /// Waiting for the metadata of the segment.
+    MetaData(Segment),
+    /// Reading data chunks from the segment.
+    RawData { format: ChunkFormat, remaining: u64 },
+    /// Skipping over bytes we don't decode.
+    Skip(u64),
+}
+
+/// A push based decoder for TDMS streams.
+///
+/// Push bytes in with [`Self::push`] in the order they are received and then call
+/// [`Self::next_event`] until it returns `None` to get the decoded events.
+///
+/// Data is decoded a chunk at a time, so samples are emitted once a full write
+/// from the writer has been received.
+///
+/// Channels with variable size data, such as strings, are not decoded. Their bytes
+/// are skipped and samples are still emitted for the other channels in the chunk.
+///
+/// # Example
+///
+/// ```rust
+/// use tedium::{DecodeEvent, StreamDecoder, TdmsWriterHandle, ChannelPath, DataLayout};
+///
+/// let mut writer = TdmsWriterHandle::from_writer(Vec::new());
+/// writer.write_channels(
+///     &[ChannelPath::new("group", "channel")],
+///     &[1.0, 2.0, 3.0],
+///     DataLayout::Contigious,
+/// ).unwrap();
+/// let bytes = writer.into_inner().unwrap();
+///
+/// let mut decoder = StreamDecoder::new();
+/// let mut samples = Vec::new();
+/// for chunk in bytes.chunks(10) {
+///     decoder.push(chunk);
+///     while let Some(event) = decoder.next_event().unwrap() {
+///         if let DecodeEvent::Samples(batch) = event {
+///             samples.extend(batch.values::<f64>().unwrap());
+///         }
+///     }
+/// }
+/// assert_eq!(samples, vec![1.0, 2.0, 3.0]);
+/// ```
+#[derive(Debug)]
+pub struct StreamDecoder {
+    index: Index,
+    buffer: Vec<u8>,
+    /// Bytes at the start of the buffer that have been decoded.
+    consumed: usize,
+    /// Position in the stream of the start of the buffer.
+    buffer_offset: u64,
+    state: DecoderState,
+    events: VecDeque<DecodeEvent>,
+}
+
+impl Default for StreamDecoder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl StreamDecoder {
+    /// Create a decoder for a new stream.
+    pub fn new() -> Self {
+        Self {
+            index: Index::new(),
+            buffer: Vec::new(),
+            consumed: 0,
+            buffer_offset: 0,
+            state: DecoderState::LeadIn,
+            events: VecDeque::new(),
+        }
+    }
+
+    /// Add bytes received from the stream.
+    pub fn push(&mut self, bytes: &[u8]) {
+        // Drop the decoded bytes so the buffer doesn't grow for the lifetime of the stream.
+        if self.consumed > 0 {
+            self.buffer.drain(..self.consumed);
+            self.buffer_offset += self.consumed as u64;
+            self.consumed = 0;
+        }
+        self.buffer.extend_from_slice(bytes);
+    }
+
+    /// Get the next event from the data pushed so far.
+    ///
+    /// Returns `None` when more data is needed.
+    ///
+    /// After an error the stream cannot be recovered.
+    pub fn next_event(&mut self) -> Result<Option<DecodeEvent>, TdmsError> {
+        loop {
+            if let Some(event) = self.events.pop_front() {
+                return Ok(Some(event));
+            }
+            if !self.advance()? {
+                return Ok(None);
+            }
+        }
+    }
+
+    /// The number of bytes decoded from the stream so far.
+    pub fn position(&self) -> u64 {
+        self.buffer_offset + self.consumed as u64
+    }
+
+    fn available(&self) -> &[u8] {
+        &self.buffer[self.consumed..]
+    }
+
+    /// Attempt to move the state machine on with the data buffered.
+    ///
+    /// Returns false if we need more data.
+    fn advance(&mut self) -> Result<bool, TdmsError> {
+        let state = std::mem::replace(&mut self.state, DecoderState::LeadIn);
+        let (next_state, progressed) = match state {
+            DecoderState::LeadIn => match self.read_lead_in()? {
+                Some(segment) => (DecoderState::MetaData(segment), true),
+                None => (DecoderState::LeadIn, false),
+            },
+            DecoderState::MetaData(segment) => {
+                if (self.available().len() as u64) < segment.raw_data_offset {
+                    (DecoderState::MetaData(segment), false)
+                } else {
+                    (self.read_meta_data(segment)?, true)
+                }
+            }
+            DecoderState::RawData { format, remaining } => {
+                if remaining < format.size {
+                    // Trailing data that doesn't make up a whole chunk.
+                    (DecoderState::Skip(remaining), true)
+                } else if (self.available().len() as u64) < format.size {
+                    (DecoderState::RawData { format, remaining }, false)
+                } else {
+                    let size = format.size as usize;
+                    let batches = format.split(&self.available()[..size])?;
+                    self.events
+                        .extend(batches.into_iter().map(DecodeEvent::Samples));
+                    self.consumed += size;
+                    let remaining = remaining - format.size;
+                    (DecoderState::RawData { format, remaining }, true)
+                }
+            }
+            DecoderState::Skip(0) => (DecoderState::LeadIn, true),
+            DecoderState::Skip(remaining) => {
+                let skip = remaining.min(self.available().len() as u64);
+                self.consumed += skip as usize;
+                (DecoderState::Skip(remaining - skip), skip > 0)
+            }
+        };
+        self.state = next_state;
+        Ok(progressed)
+    }
+
+    fn read_lead_in(&mut self) -> Result<Option<Segment>, TdmsError> {
+        let available = self.available();
+        if available.len() < LEAD_IN_BYTES as usize {
+            return Ok(None);
+        }
+
+        let mut tag = [0u8; 4];
+        tag.copy_from_slice(&available[0..4]);
+        if tag != SEGMENT_TAG {
+            return Err(TdmsError::HeaderPatternNotMatched(tag));
+        }
+
+        //ToC is always little endian.
+        let mut toc = [0u8; 4];
+        toc.copy_from_slice(&available[4..8]);
+        let toc = ToC::from_u32(u32::from_le_bytes(toc));
+
+        let mut cursor = Cursor::new(&available[8..LEAD_IN_BYTES as usize]);
+        let (next_segment_offset, raw_data_offset) = match toc.big_endian {
+            true => read_offsets(&mut BigEndianReader::from_reader(&mut cursor))?,
+            false => read_offsets(&mut LittleEndianReader::from_reader(&mut cursor))?,
+        };
+
+        if raw_data_offset > next_segment_offset {
+            return Err(TdmsError::InvalidRawOffset);
+        }
+
+        self.events.push_back(DecodeEvent::SegmentStart {
+            offset: self.position(),
+            toc,
+            next_segment_offset,
+            raw_data_offset,
+        });
+        self.consumed += LEAD_IN_BYTES as usize;
+
+        Ok(Some(Segment {
+            toc,
+            next_segment_offset,
+            raw_data_offset,
+            meta_data: None,
+        }))
+    }
+
+    /// Read the metadata once it is all available and work out the next state.
+    fn read_meta_data(&mut self, mut segment: Segment) -> Result<DecoderState, TdmsError> {
+        let meta_length = segment.raw_data_offset as usize;
+
+        if segment.toc.contains_meta_data {
+            let mut cursor = Cursor::new(&self.available()[..meta_length]);
+            let meta: MetaData = match segment.toc.big_endian {
+                true => BigEndianReader::from_reader(&mut cursor).read_meta()?,
+                false => LittleEndianReader::from_reader(&mut cursor).read_meta()?,
+            };
+            self.queue_meta_data_events(&meta)?;
+            segment.meta_data = Some(meta);
+        }
+        self.consumed += meta_length;
+
+        let data_length = segment.next_segment_offset - segment.raw_data_offset;
+        let contains_raw_data = segment.toc.contains_raw_data;
+        self.index.add_segment(segment)?;
+
+        if !contains_raw_data {
+            return Ok(DecoderState::Skip(data_length));
+        }
+
+        let block = self
+            .index
+            .last_data_block()
+            .expect("A data block must exist after adding a segment with raw data.");
+        let paths = self
+            .index
+            .active_paths()
+            .map(ChannelPath::try_from)
+            .collect::<Result<Vec<_>, _>>()?;
+        let format = ChunkFormat::new(block, paths)?;
+
+        if format.size == 0 || !format.has_samples() {
+            return Ok(DecoderState::Skip(data_length));
+        }
+
+        Ok(DecoderState::RawData {
+            format,
+            remaining: data_length,
+        })
+    }
+
+    fn queue_meta_data_events(&mut self, meta: &MetaData) -> Result<(), TdmsError> {
+        for object in &meta.objects {
+            let path = PropertyPath::try_from(object.path.as_str())?;
+            self.events.push_back(DecodeEvent::Object {
+                path: path.clone(),
+                has_data: object.raw_data_index != RawDataIndex::None,
+            });
+            for (name, value) in &object.properties {
+                self.events.push_back(DecodeEvent::Property {
+                    path: path.clone(),
+                    name: name.clone(),
+                    value: value.clone(),
+                });
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Read the version and offsets following the ToC.
+fn read_offsets<R: std::io::Read + std::io::Seek>(
+    reader: &mut impl TdmsReader<R>,
+) -> Result<(u64, u64), TdmsError> {
+    let _version: u32 = reader.read_value()?;
+    let next_segment_offset: u64 = reader.read_value()?;
+    let raw_data_offset: u64 = reader.read_value()?;
+    Ok((next_segment_offset, raw_data_offset))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::TdmsWriterHandle;
+    use crate::io::writer::{LittleEndianWriter, TdmsWriter};
+    use crate::meta_data::{ObjectMetaData, RawDataMeta};
+
+    /// Write a segment with the given channels and raw bytes.
+    fn raw_segment(channels: &[(&str, RawDataMeta)], interleaved: bool, data: &[u8]) -> Vec<u8> {
+        let meta = MetaData {
+            objects: channels
+                .iter()
+                .map(|(path, meta)| ObjectMetaData {
+                    path: path.to_string(),
+                    properties: vec![],
+                    raw_data_index: RawDataIndex::RawData(meta.clone()),
+                })
+                .collect(),
+        };
+        let toc = ToC {
+            contains_new_object_list: true,
+            data_is_interleaved: interleaved,
+            ..Default::default()
+        };
+        let mut writer = LittleEndianWriter::from_writer(Vec::new());
+        writer.write_segment(toc, Some(meta), Some(data)).unwrap();
+        writer.into_inner().unwrap()
+    }
+
+    fn u32_meta(number_of_values: u64) -> RawDataMeta {
+        RawDataMeta {
+            data_type: DataType::U32,
+            number_of_values,
+            total_size_bytes: None,
+        }
+    }
+
+    fn u32_bytes(values: &[u32]) -> Vec<u8> {
+        values
+            .iter()
+            .flat_map(|value| value.to_le_bytes())
+            .collect()
+    }
+
+    fn write_test_stream() -> Vec<u8> {
+        let mut writer = TdmsWriterHandle::from_writer(Vec::new());
+        writer
+            .write_properties(
+                &PropertyPath::group("group"),
+                &[("name", PropertyValue::String("test".to_string()))],
+            )
+            .unwrap();
+        writer
+            .write_channels(
+                &[
+                    ChannelPath::new("group", "ch1"),
+                    ChannelPath::new("group", "ch2"),
+                ],
+                &[1.0, 2.0, 3.0, 4.0],
+                DataLayout::Interleaved,
+            )
+            .unwrap();
+        writer
+            .write_channels(
+                &[
+                    ChannelPath::new("group", "ch1"),
+                    ChannelPath::new("group", "ch2"),
+                ],
+                &[5.0, 6.0, 7.0, 8.0],
+                DataLayout::Interleaved,
+            )
+            .unwrap();
+        writer
+            .write_channels(
+                &[ChannelPath::new("group", "ch3")],
+                &[1u32, 2, 3],
+                DataLayout::Contigious,
+            )
+            .unwrap();
+        writer.into_inner().unwrap()
+    }
+
+    fn decode_all(bytes: &[u8], chunk_size: usize) -> Vec<DecodeEvent> {
+        let mut decoder = StreamDecoder::new();
+        let mut events = Vec::new();
+        for chunk in bytes.chunks(chunk_size) {
+            decoder.push(chunk);
+            while let Some(event) = decoder.next_event().unwrap() {
+                events.push(event);
+            }
+        }
+        assert_eq!(decoder.position(), bytes.len() as u64);
+        events
+    }
+
+    fn channel_values<D: TdmsStorageType>(events: &[DecodeEvent], channel: &ChannelPath) -> Vec<D> {
+        events
+            .iter()
+            .filter_map(|event| match event {
+                DecodeEvent::Samples(batch) if batch.channel() == channel => {
+                    Some(batch.values::<D>().unwrap())
+                }
+                _ => None,
+            })
+            .flatten()
+            .collect()
+    }
+
+    #[test]
+    fn test_decode_byte_by_byte() {
+        let bytes = write_test_stream();
+        let events = decode_all(&bytes, 1);
+
+        let ch1: Vec<f64> = channel_values(&events, &ChannelPath::new("group", "ch1"));
+        let ch2: Vec<f64> = channel_values(&events, &ChannelPath::new("group", "ch2"));
+        let ch3: Vec<u32> = channel_values(&events, &ChannelPath::new("group", "ch3"));
+        assert_eq!(ch1, vec![1.0, 3.0, 5.0, 7.0]);
+        assert_eq!(ch2, vec![2.0, 4.0, 6.0, 8.0]);
+        assert_eq!(ch3, vec![1, 2, 3]);
+    }
+
+    #[test]
+    fn test_decode_single_push_matches_small_pushes() {
+        let bytes = write_test_stream();
+        assert_eq!(decode_all(&bytes, bytes.len()), decode_all(&bytes, 7));
+    }
+
+    #[test]
+    fn test_decode_segment_and_meta_events() {
+        let bytes = write_test_stream();
+        let events = decode_all(&bytes, 64);
+
+        let segments = events
+            .iter()
+            .filter(|event| matches!(event, DecodeEvent::SegmentStart { .. }))
+            .count();
+        assert_eq!(segments, 4);
+
+        assert!(matches!(
+            &events[0],
+            DecodeEvent::SegmentStart { offset: 0, toc, .. } if toc.contains_meta_data && !toc.contains_raw_data
+        ));
+        assert_eq!(
+            events[1],
+            DecodeEvent::Object {
+                path: PropertyPath::group("group"),
+                has_data: false
+            }
+        );
+        assert_eq!(
+            events[2],
+            DecodeEvent::Property {
+                path: PropertyPath::group("group"),
+                name: "name".to_string(),
+                value: PropertyValue::String("test".to_string())
+            }
+        );
+    }
+
+    #[test]
+    fn test_decode_type_mismatch() {
+        let bytes = write_test_stream();
+        let events = decode_all(&bytes, 64);
+        let batch = events
+            .iter()
+            .find_map(|event| match event {
+                DecodeEvent::Samples(batch) => Some(batch),
+                _ => None,
+            })
+            .unwrap();
+        assert!(matches!(
+            batch.values::<u32>(),
+            Err(TdmsError::DataTypeMismatch(
+                DataType::DoubleFloat,
+                DataType::U32
+            ))
+        ));
+    }
+
+    #[test]
+    fn test_decode_bad_header() {
+        let mut decoder = StreamDecoder::new();
+        decoder.push(&[0u8; 28]);
+        assert!(matches!(
+            decoder.next_event(),
+            Err(TdmsError::HeaderPatternNotMatched(_))
+        ));
+    }
+
+    #[test]
+    fn test_decode_skips_string_channels() {
+        // Strings are stored as the end offset of each string followed by the text.
+        let mut strings = u32_bytes(&[2, 5]);
+        strings.extend_from_slice(b"abcde");
+        let string_meta = RawDataMeta {
+            data_type: DataType::TdmsString,
+            number_of_values: 2,
+            total_size_bytes: Some(strings.len() as u64),
+        };
+
+        let mut data = u32_bytes(&[1, 2, 3]);
+        data.extend_from_slice(&strings);
+        let mut bytes = raw_segment(
+            &[
+                ("/'group'/'ch1'", u32_meta(3)),
+                ("/'group'/'name'", string_meta),
+            ],
+            false,
+            &data,
+        );
+        bytes.extend(raw_segment(
+            &[("/'group'/'ch1'", u32_meta(2))],
+            false,
+            &u32_bytes(&[4, 5]),
+        ));
+
+        let events = decode_all(&bytes, 5);
+        let ch1: Vec<u32> = channel_values(&events, &ChannelPath::new("group", "ch1"));
+        assert_eq!(ch1, vec![1, 2, 3, 4, 5]);
+        assert!(!events.iter().any(|event| matches!(
+            event,
+            DecodeEvent::Samples(batch) if batch.data_type() == DataType::TdmsString
+        )));
+    }
+
+    #[test]
+    fn test_decode_uneven_interleaved_block_errors() {
+        let bytes = raw_segment(
+            &[
+                ("/'group'/'ch1'", u32_meta(2)),
+                ("/'group'/'ch2'", u32_meta(3)),
+            ],
+            true,
+            &u32_bytes(&[1, 2, 3, 4, 5]),
+        );
+
+        let mut decoder = StreamDecoder::new();
+        decoder.push(&bytes);
+        let result = std::iter::from_fn(|| decoder.next_event().transpose())
+            .find(|event| event.is_err())
+            .unwrap();
+        assert!(matches!(result, Err(TdmsError::InterleavedLengthMismatch)));
+    }
+
+    #[test]
+    fn test_split_short_chunk_errors() {
+        let format = ChunkFormat {
+            channels: vec![ChunkChannel {
+                path: ChannelPath::new("group", "ch1"),
+                data_type: DataType::U32,
+                number_of_values: 4,
+                size: 16,
+                variable_size: false,
+            }],
+            layout: DataLayout::Interleaved,
+            byte_order: Endianess::Little,
+            size: 16,
+        };
+        assert!(matches!(
+            format.split(&[0u8; 10]),
+            Err(TdmsError::EndOfFile)
+        ));
+    }
+}
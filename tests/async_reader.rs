@@ -0,0 +1,54 @@
+//! Validate reading files on disk with the tokio reader.
+//!
+#![cfg(feature = "tokio")]
+mod common;
+
+use common::TempPath;
+use tedium::{AsyncTdmsFile, ChannelPath, DataLayout, PropertyPath, PropertyValue, TdmsFile};
+
+fn write_test_file(path: &TempPath) {
+    let mut file = TdmsFile::create(&path.0).unwrap();
+    let mut writer = file.writer().unwrap();
+    writer
+        .write_properties(
+            &PropertyPath::group("group"),
+            &[("unit", PropertyValue::String("V".to_string()))],
+        )
+        .unwrap();
+    for block in 0..20 {
+        let values: Vec<f64> = (0..100).map(|i| (block * 100 + i) as f64).collect();
+        writer
+            .write_channels(
+                &[ChannelPath::new("group", "ch1")],
+                &values,
+                DataLayout::Contigious,
+            )
+            .unwrap();
+    }
+}
+
+#[tokio::test]
+async fn test_async_read_channel() {
+    let path = TempPath::new("async-read");
+    write_test_file(&path);
+
+    let mut file = AsyncTdmsFile::open(&path.0).await.unwrap();
+    let channel = ChannelPath::new("group", "ch1");
+    assert_eq!(file.channel_length(&channel), Some(2000));
+    assert_eq!(
+        file.read_property(&PropertyPath::group("group"), "unit")
+            .unwrap(),
+        Some(&PropertyValue::String("V".to_string()))
+    );
+
+    let mut output = vec![0.0f64; 2000];
+    file.read_channel(&channel, &mut output).await.unwrap();
+    let expected: Vec<f64> = (0..2000).map(|i| i as f64).collect();
+    assert_eq!(output, expected);
+
+    let mut output = vec![0.0f64; 10];
+    file.read_channel_from(&channel, 1995, &mut output)
+        .await
+        .unwrap();
+    assert_eq!(&output[..5], &[1995.0, 1996.0, 1997.0, 1998.0, 1999.0]);
+}
@@ -0,0 +1,42 @@
+//! Validate files written by the tokio writer can be read back.
+//!
+#![cfg(feature = "tokio")]
+mod common;
+
+use common::TempPath;
+use tedium::{AsyncTdmsFileWriter, ChannelPath, DataLayout, PropertyPath, PropertyValue, TdmsFile};
+
+#[tokio::test]
+async fn test_async_write_then_load() {
+    let path = TempPath::new("async-write");
+    let channel = ChannelPath::new("group", "ch1");
+
+    let mut writer = AsyncTdmsFileWriter::create(&path.0).await.unwrap();
+    writer
+        .write_properties(
+            &PropertyPath::channel("group", "ch1"),
+            &[("unit_string", PropertyValue::String("V".to_string()))],
+        )
+        .await
+        .unwrap();
+    for block in 0..10 {
+        let values: Vec<f64> = (0..100).map(|i| (block * 100 + i) as f64).collect();
+        writer
+            .write_channels(&[&channel], &values, DataLayout::Contigious)
+            .await
+            .unwrap();
+    }
+    writer.into_inner().await.unwrap();
+
+    let mut file = TdmsFile::load(&path.0).unwrap();
+    assert_eq!(file.channel_length(&channel), Some(1000));
+    assert_eq!(
+        file.read_property(&PropertyPath::channel("group", "ch1"), "unit_string")
+            .unwrap(),
+        Some(&PropertyValue::String("V".to_string()))
+    );
+    let mut output = vec![0.0f64; 1000];
+    file.read_channel(&channel, &mut output).unwrap();
+    let expected: Vec<f64> = (0..1000).map(|i| i as f64).collect();
+    assert_eq!(output, expected);
+}
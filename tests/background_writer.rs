@@ -0,0 +1,122 @@
+//! Validate the background writer front end.
+//!
+mod common;
+
+use common::get_empty_file;
+use tedium::{BackgroundWriter, ChannelPath, DataLayout, PropertyPath, PropertyValue, TdmsError};
+
+#[test]
+fn test_background_writer_multiple_producers() {
+    let writer = get_empty_file().into_writer().unwrap();
+    let background = BackgroundWriter::spawn(writer, 4).unwrap();
+
+    let producers: Vec<_> = ["ch1", "ch2"]
+        .into_iter()
+        .map(|channel| {
+            let queue = background.queue();
+            std::thread::spawn(move || {
+                for block in 0..10 {
+                    queue
+                        .write_channels(
+                            vec![ChannelPath::new("group", channel)],
+                            vec![block as f64; 10],
+                            DataLayout::Contigious,
+                        )
+                        .unwrap();
+                }
+            })
+        })
+        .collect();
+
+    for producer in producers {
+        producer.join().unwrap();
+    }
+
+    let queue = background.queue();
+    queue
+        .write_properties(
+            PropertyPath::group("group"),
+            vec![(
+                "name".to_string(),
+                PropertyValue::String("test".to_string()),
+            )],
+        )
+        .unwrap();
+    queue.flush().unwrap().wait().unwrap();
+
+    let mut file = background.finish().unwrap().into_file().unwrap();
+
+    for channel in ["ch1", "ch2"] {
+        let path = ChannelPath::new("group", channel);
+        assert_eq!(file.channel_length(&path), Some(100));
+        let mut output = vec![0.0f64; 100];
+        file.read_channel(&path, &mut output[..]).unwrap();
+        let expected: Vec<f64> = (0..10)
+            .flat_map(|block| std::iter::repeat_n(block as f64, 10))
+            .collect();
+        assert_eq!(output, expected);
+    }
+    assert_eq!(
+        file.read_property(&PropertyPath::group("group"), "name")
+            .unwrap(),
+        Some(&PropertyValue::String("test".to_string()))
+    );
+}
+
+fn stopped_by_no_channels<T>(result: Result<T, TdmsError>) -> bool {
+    match result {
+        Err(TdmsError::BackgroundWriterFailed(cause)) => matches!(*cause, TdmsError::NoChannels),
+        _ => false,
+    }
+}
+
+#[test]
+fn test_background_writer_reports_errors() {
+    let writer = get_empty_file().into_writer().unwrap();
+    let background = BackgroundWriter::spawn(writer, 4).unwrap();
+    let queue = background.queue();
+
+    // No channels is an error on the writer thread.
+    queue
+        .write_channels(Vec::new(), vec![1.0f64], DataLayout::Contigious)
+        .unwrap();
+
+    // The cause is returned to the producers as well as from finish.
+    let flush = queue.flush();
+    assert!(stopped_by_no_channels(
+        flush.and_then(|handle| handle.wait())
+    ));
+    assert!(queue.is_stopped());
+    assert!(stopped_by_no_channels(queue.write_channels(
+        vec![ChannelPath::new("group", "ch1")],
+        vec![1.0f64],
+        DataLayout::Contigious
+    )));
+
+    assert!(stopped_by_no_channels(background.finish()));
+    assert!(matches!(
+        queue.flush(),
+        Err(TdmsError::BackgroundWriterFailed(_))
+    ));
+}
+
+#[test]
+fn test_background_writer_try_write() {
+    let writer = get_empty_file().into_writer().unwrap();
+    let background = BackgroundWriter::spawn(writer, 4).unwrap();
+    let queue = background.queue();
+
+    queue
+        .try_write_channels(
+            vec![ChannelPath::new("group", "ch1")],
+            vec![1u32, 2, 3],
+            DataLayout::Contigious,
+        )
+        .unwrap();
+
+    let mut file = background.finish().unwrap().into_file().unwrap();
+    let mut output = vec![0u32; 3];
+    file.read_channel(&ChannelPath::new("group", "ch1"), &mut output[..])
+        .unwrap();
+    assert_eq!(output, vec![1, 2, 3]);
+}
@@ -0,0 +1,122 @@
+//! Reads through the block cache should match uncached reads.
+use std::io::Cursor;
+
+use tedium::{ChannelPath, DataLayout, TdmsError, TdmsFile};
+
+fn build_file(layout: DataLayout) -> TdmsFile<Cursor<Vec<u8>>> {
+    let mut file = TdmsFile::new(Cursor::new(Vec::new())).unwrap();
+    let channels = [
+        ChannelPath::new("group", "ch1"),
+        ChannelPath::new("group", "ch2"),
+    ];
+    let mut writer = file.writer().unwrap();
+    for block in 0..10 {
+        let values: Vec<f64> = (0..200).map(|i| (block * 1000 + i) as f64).collect();
+        writer.write_channels(&channels, &values, layout).unwrap();
+    }
+    drop(writer);
+    file
+}
+
+fn read_both(file: &mut TdmsFile<Cursor<Vec<u8>>>, start: u64, length: usize) -> Vec<Vec<f64>> {
+    let channels = [
+        ChannelPath::new("group", "ch2"),
+        ChannelPath::new("group", "ch1"),
+    ];
+    let mut output1 = vec![0.0f64; length];
+    let mut output2 = vec![0.0f64; length];
+    file.read_channels_from(&channels, start, &mut [&mut output1, &mut output2])
+        .unwrap();
+    vec![output1, output2]
+}
+
+#[test]
+fn test_cached_reads_match_uncached() {
+    for layout in [DataLayout::Contigious, DataLayout::Interleaved] {
+        let mut file = build_file(layout);
+        let expected = read_both(&mut file, 150, 600);
+
+        file.enable_block_cache(1024 * 1024);
+        assert_eq!(read_both(&mut file, 150, 600), expected);
+        assert_eq!(read_both(&mut file, 150, 600), expected);
+
+        let stats = file.block_cache_stats().unwrap();
+        // 2 channels over 7 blocks.
+        assert_eq!(stats.misses, 14);
+        assert_eq!(stats.hits, 14);
+        assert_eq!(stats.entries, 14);
+        assert_eq!(stats.bytes, 14 * 100 * 8);
+    }
+}
+
+#[test]
+fn test_cache_respects_byte_budget() {
+    let mut file = build_file(DataLayout::Contigious);
+    let expected = read_both(&mut file, 0, 1000);
+
+    // Room for 3 channel blocks.
+    file.enable_block_cache(3 * 100 * 8);
+    assert_eq!(read_both(&mut file, 0, 1000), expected);
+
+    let stats = file.block_cache_stats().unwrap();
+    assert_eq!(stats.entries, 3);
+    assert!(stats.bytes <= stats.max_bytes);
+}
+
+#[test]
+fn test_blocks_larger_than_cache_are_read_directly() {
+    let mut file = build_file(DataLayout::Interleaved);
+    let expected = read_both(&mut file, 50, 300);
+
+    file.enable_block_cache(16);
+    assert_eq!(read_both(&mut file, 50, 300), expected);
+    assert_eq!(file.block_cache_stats().unwrap().entries, 0);
+}
+
+#[test]
+fn test_cache_sees_new_blocks_after_writing() {
+    let mut file = build_file(DataLayout::Contigious);
+    file.enable_block_cache(1024 * 1024);
+    read_both(&mut file, 0, 1000);
+
+    let mut writer = file.writer().unwrap();
+    writer
+        .write_channels(
+            &[
+                ChannelPath::new("group", "ch1"),
+                ChannelPath::new("group", "ch2"),
+            ],
+            &[-1.0, -2.0],
+            DataLayout::Contigious,
+        )
+        .unwrap();
+    drop(writer);
+
+    let output = read_both(&mut file, 999, 2);
+    assert_eq!(output[0], vec![9199.0, -2.0]);
+    assert_eq!(output[1], vec![9099.0, -1.0]);
+}
+
+#[test]
+fn test_disable_block_cache() {
+    let mut file = build_file(DataLayout::Contigious);
+    assert_eq!(file.block_cache_stats(), None);
+    file.enable_block_cache(1024);
+    assert!(file.block_cache_stats().is_some());
+    file.disable_block_cache();
+    assert_eq!(file.block_cache_stats(), None);
+}
+
+#[test]
+fn test_cached_block_checks_type() {
+    let mut file = build_file(DataLayout::Contigious);
+    file.enable_block_cache(1024 * 1024);
+    let channel = ChannelPath::new("group", "ch1");
+    let mut output = vec![0.0f64; 10];
+    file.read_channel(&channel, &mut output).unwrap();
+
+    // The block is now cached but must still be read as the channel's type.
+    let mut wrong_type = vec![0i32; 10];
+    let result = file.read_channel(&channel, &mut wrong_type);
+    assert!(matches!(result, Err(TdmsError::DataTypeMismatch(..))));
+}
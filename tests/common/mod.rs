@@ -9,6 +9,26 @@ pub fn open_test_file() -> TdmsFile<File> {
     TdmsFile::load(&path).unwrap()
 }
 
+/// A path in the temp directory which is removed when dropped.
+///
+/// Any file left at the path by an earlier run is removed first.
+pub struct TempPath(pub PathBuf);
+
+impl TempPath {
+    pub fn new(name: &str) -> Self {
+        let mut path = std::env::temp_dir();
+        path.push(format!("tedium-{}-{name}.tdms", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        Self(path)
+    }
+}
+
+impl Drop for TempPath {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.0);
+    }
+}
+
 pub fn get_empty_file() -> TdmsFile<Cursor<Vec<u8>>> {
     // use a software buffer for speed.
 
@@ -0,0 +1,60 @@
+//! Validate syncing written data to disk.
+//!
+mod common;
+
+use common::TempPath;
+use tedium::{ChannelPath, DataLayout, SyncPolicy, TdmsFile};
+
+fn file_length(path: &TempPath) -> u64 {
+    std::fs::metadata(&path.0).unwrap().len()
+}
+
+#[test]
+fn test_sync_data_reports_synced_length() {
+    let path = TempPath::new("sync-data");
+    let mut file = TdmsFile::create(&path.0).unwrap();
+    let mut writer = file.writer().unwrap();
+    assert_eq!(writer.synced_length(), None);
+
+    writer
+        .write_channels(
+            &[ChannelPath::new("group", "ch1")],
+            &[1.0, 2.0, 3.0],
+            DataLayout::Contigious,
+        )
+        .unwrap();
+    writer.sync_data().unwrap();
+
+    assert_eq!(writer.synced_length(), Some(file_length(&path)));
+}
+
+#[test]
+fn test_sync_policy_every_segments() {
+    let path = TempPath::new("sync-policy");
+    let file = TdmsFile::create(&path.0).unwrap();
+    let mut writer = file.into_writer().unwrap();
+    writer.set_sync_policy(SyncPolicy::default().every_segments(2));
+
+    let channel = [ChannelPath::new("group", "ch1")];
+    writer
+        .write_channels(&channel, &[1.0, 2.0], DataLayout::Contigious)
+        .unwrap();
+    assert_eq!(writer.synced_length(), None);
+    // Still buffered until the policy syncs.
+    assert_eq!(file_length(&path), 0);
+
+    writer
+        .write_channels(&channel, &[3.0, 4.0], DataLayout::Contigious)
+        .unwrap();
+    let synced = writer.synced_length().unwrap();
+    assert_eq!(synced, file_length(&path));
+
+    writer
+        .write_channels(&channel, &[5.0, 6.0], DataLayout::Contigious)
+        .unwrap();
+    assert_eq!(writer.synced_length(), Some(synced));
+
+    writer.sync_all().unwrap();
+    assert_eq!(writer.synced_length(), Some(file_length(&path)));
+    assert!(file_length(&path) > synced);
+}
@@ -0,0 +1,59 @@
+//! Copy the test file leaving out channels and properties.
+mod common;
+
+use std::io::Cursor;
+
+use tedium::{ChannelPath, CopyFilter, PropertyPath, TdmsFile};
+
+fn read(
+    file: &mut TdmsFile<impl std::io::Read + std::io::Seek>,
+    channel: &ChannelPath,
+) -> Vec<f64> {
+    let mut output = vec![0.0; file.channel_length(channel).unwrap() as usize];
+    file.read_channel(channel, &mut output).unwrap();
+    output
+}
+
+#[test]
+fn test_copy_without_channel() {
+    let mut file = common::open_test_file();
+    let removed = ChannelPath::new("structure", "ch2");
+
+    let mut output = Cursor::new(Vec::new());
+    file.copy_filtered(&mut output, &CopyFilter::new().remove_channel(&removed))
+        .unwrap();
+    let mut copy = TdmsFile::new(output).unwrap();
+
+    assert_eq!(copy.channel_length(&removed), None);
+    for name in ["ch1", "ch3", "ch4", "ch5", "ch6"] {
+        let channel = ChannelPath::new("structure", name);
+        assert_eq!(read(&mut copy, &channel), read(&mut file, &channel));
+    }
+}
+
+#[test]
+fn test_copy_without_properties() {
+    let mut file = common::open_test_file();
+    let filter = CopyFilter::new()
+        .remove_property(&PropertyPath::file(), "i8")
+        .remove_property_everywhere("u8");
+
+    let mut output = Cursor::new(Vec::new());
+    file.copy_filtered(&mut output, &filter).unwrap();
+    let copy = TdmsFile::new(output).unwrap();
+
+    let group = PropertyPath::group("group");
+    assert_eq!(
+        copy.read_property(&PropertyPath::file(), "i8").unwrap(),
+        None
+    );
+    assert_eq!(copy.read_property(&group, "u8").unwrap(), None);
+    assert_eq!(
+        copy.read_property(&group, "i8").unwrap(),
+        file.read_property(&group, "i8").unwrap()
+    );
+    assert_eq!(
+        copy.read_property(&PropertyPath::file(), "u16").unwrap(),
+        file.read_property(&PropertyPath::file(), "u16").unwrap()
+    );
+}
@@ -0,0 +1,130 @@
+//! Inspect the segment and data block structure of written files.
+use std::io::Cursor;
+
+use tedium::{ChannelPath, DataLayout, DataType, Endianess, PropertyPath, PropertyValue, TdmsFile};
+
+fn build_file() -> TdmsFile<Cursor<Vec<u8>>> {
+    let mut file = TdmsFile::new(Cursor::new(Vec::new())).unwrap();
+    let channels = [
+        ChannelPath::new("group", "ch1"),
+        ChannelPath::new("group", "ch2"),
+    ];
+    let mut writer = file.writer().unwrap();
+    writer
+        .write_properties(
+            &PropertyPath::group("group"),
+            &[("unit", PropertyValue::String("V".to_string()))],
+        )
+        .unwrap();
+    writer
+        .write_channels(&channels, &[1.0f64; 20], DataLayout::Interleaved)
+        .unwrap();
+    writer
+        .write_channels(&channels, &[2.0f64; 20], DataLayout::Interleaved)
+        .unwrap();
+    drop(writer);
+    file
+}
+
+#[test]
+fn test_segments_cover_the_file() {
+    let mut file = build_file();
+    let segments = file.segments().unwrap();
+    assert_eq!(segments.len(), 3);
+
+    assert!(!segments[0].toc.contains_raw_data);
+    assert_eq!(segments[0].objects.len(), 1);
+    assert_eq!(segments[0].objects[0].path, "/'group'");
+    assert_eq!(segments[0].objects[0].property_count, 1);
+    assert_eq!(segments[0].data_block, None);
+
+    assert!(segments[1].toc.data_is_interleaved);
+    assert_eq!(segments[1].raw_data_size(), 160);
+    assert_eq!(segments[1].data_block, Some(0));
+    assert_eq!(segments[2].data_block, Some(1));
+
+    for pair in segments.windows(2) {
+        assert_eq!(
+            pair[0].offset + 28 + pair[0].next_segment_offset,
+            pair[1].offset
+        );
+    }
+}
+
+#[test]
+fn test_data_blocks_match_segments() {
+    let mut file = build_file();
+    let blocks: Vec<_> = file.data_blocks().collect();
+    assert_eq!(blocks.len(), 2);
+
+    for (block, segment) in blocks.iter().zip(&file.segments().unwrap()[1..]) {
+        assert_eq!(block.start, segment.raw_data_start());
+        assert_eq!(block.length, segment.raw_data_size());
+        assert_eq!(block.layout, DataLayout::Interleaved);
+        assert_eq!(block.byte_order, Endianess::Little);
+        assert_eq!(block.number_of_chunks, 1);
+
+        let paths: Vec<_> = block.channels.iter().map(|c| c.path.as_str()).collect();
+        assert_eq!(paths, ["/'group'/'ch1'", "/'group'/'ch2'"]);
+        assert_eq!(block.channels[0].format.data_type, DataType::DoubleFloat);
+        assert_eq!(block.channels[0].format.number_of_values, 10);
+    }
+    assert_eq!(file.data_block(1), Some(blocks[1].clone()));
+    assert_eq!(file.data_block(2), None);
+}
+
+#[test]
+fn test_channel_data_locations() {
+    let file = build_file();
+    let locations = file
+        .channel_data_locations(&ChannelPath::new("group", "ch2"))
+        .unwrap();
+    assert_eq!(locations.len(), 2);
+    assert_eq!(locations[0].data_block, 0);
+    assert_eq!(locations[0].channel_index, 1);
+    assert_eq!(locations[0].number_of_samples, 10);
+    assert_eq!(locations[1].data_block, 1);
+
+    assert_eq!(
+        file.channel_data_locations(&ChannelPath::new("group", "missing")),
+        None
+    );
+}
+
+#[test]
+fn test_channel_info() {
+    let mut file = build_file();
+    let channel = ChannelPath::new("group", "ch1");
+    file.writer()
+        .unwrap()
+        .write_properties(
+            &PropertyPath::channel("group", "ch1"),
+            &[("gain", PropertyValue::DoubleFloat(2.0))],
+        )
+        .unwrap();
+    file.big_endian_writer()
+        .unwrap()
+        .write_channels(&[&channel], &[3.0f64; 5], DataLayout::Contigious)
+        .unwrap();
+
+    let info = file.channel_info(&channel).unwrap();
+    assert_eq!(info.data_type, Some(DataType::DoubleFloat));
+    assert!(!info.data_type_changed);
+    assert_eq!(info.length, 25);
+    assert_eq!(info.number_of_blocks, 3);
+    assert_eq!(
+        info.layouts,
+        [DataLayout::Interleaved, DataLayout::Contigious]
+    );
+    assert_eq!(info.byte_orders, [Endianess::Little, Endianess::Big]);
+    assert_eq!(
+        info.properties.get("gain"),
+        Some(&PropertyValue::DoubleFloat(2.0))
+    );
+    assert_eq!(info.length, file.channel_length(&channel).unwrap());
+
+    assert_eq!(
+        file.channel_info(&ChannelPath::new("group", "missing")),
+        None
+    );
+}
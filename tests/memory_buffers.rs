@@ -1,5 +1,5 @@
 use std::io::Cursor;
-use tedium::{ChannelPath, DataLayout, TdmsFile};
+use tedium::{ChannelPath, DataLayout, PropertyPath, PropertyValue, TdmsFile, TdmsWriterHandle};
 
 #[test]
 fn test_can_write_and_read_from_buffer() {
@@ -39,3 +39,52 @@ fn test_can_write_and_read_from_buffer() {
         .unwrap();
     assert_eq!(output_buffer, data_to_write);
 }
+
+/// A sink which only implements [`Write`], like a socket or pipe.
+struct WriteOnlySink(Vec<u8>);
+
+impl std::io::Write for WriteOnlySink {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0.write(buf)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+#[test]
+fn test_can_write_to_write_only_sink() {
+    let mut writer = TdmsWriterHandle::from_writer(WriteOnlySink(Vec::new()));
+
+    writer
+        .write_properties(
+            &PropertyPath::file(),
+            &[("name", PropertyValue::String("stream".to_string()))],
+        )
+        .unwrap();
+    for block in 0..2 {
+        writer
+            .write_channels(
+                &[
+                    ChannelPath::new("group", "ch1"),
+                    ChannelPath::new("group", "ch2"),
+                ],
+                &[block as f64; 6],
+                DataLayout::Interleaved,
+            )
+            .unwrap();
+    }
+
+    let sink = writer.into_inner().unwrap();
+
+    let mut file = TdmsFile::new(Cursor::new(sink.0)).unwrap();
+    assert_eq!(
+        file.read_property(&PropertyPath::file(), "name").unwrap(),
+        Some(&PropertyValue::String("stream".to_string()))
+    );
+    let mut output = vec![0.0; 6];
+    file.read_channel(&ChannelPath::new("group", "ch2"), &mut output[..])
+        .unwrap();
+    assert_eq!(output, vec![0.0, 0.0, 0.0, 1.0, 1.0, 1.0]);
+}
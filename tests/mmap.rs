@@ -0,0 +1,149 @@
+//! Validate reading files through a memory map.
+//!
+#![cfg(feature = "mmap")]
+mod common;
+
+use std::fs::File;
+
+use common::TempPath;
+use tedium::{ChannelPath, DataLayout, MmapTdmsFile, TdmsError, TdmsFile, TdmsStorageType};
+
+fn channels() -> [ChannelPath; 2] {
+    [
+        ChannelPath::new("group", "ch1"),
+        ChannelPath::new("group", "ch2"),
+    ]
+}
+
+/// Write three segments, each with its own data block holding one chunk of both channels.
+fn write_file<D: TdmsStorageType + Copy>(
+    path: &TempPath,
+    values: &[D],
+    layout: DataLayout,
+) -> TdmsFile<File> {
+    let mut file = TdmsFile::create(&path.0).unwrap();
+    let mut writer = file.writer().unwrap();
+    for _ in 0..3 {
+        writer.write_channels(&channels(), values, layout).unwrap();
+    }
+    drop(writer);
+    file
+}
+
+#[test]
+fn test_fast_path_matches_standard_read() {
+    let path = TempPath::new("mmap-fast");
+    let values: Vec<f64> = (0..200).map(|i| i as f64).collect();
+    let mut file = write_file(&path, &values, DataLayout::Contigious);
+    let mapped = MmapTdmsFile::open(&path.0).unwrap();
+
+    for start in [0, 50, 150] {
+        let mut expected = vec![0.0f64; 250];
+        let mut actual = vec![0.0f64; 250];
+        file.read_channel_from(&channels()[1], start, &mut expected)
+            .unwrap();
+        mapped
+            .read_channel_from(&channels()[1], start, &mut actual)
+            .unwrap();
+        assert_eq!(actual, expected);
+    }
+}
+
+#[test]
+fn test_interleaved_uses_fallback() {
+    let path = TempPath::new("mmap-interleaved");
+    let values: Vec<i32> = (0..20).collect();
+    write_file(&path, &values, DataLayout::Interleaved);
+    let mapped = MmapTdmsFile::open(&path.0).unwrap();
+
+    let mut ch1 = vec![0i32; 30];
+    let mut ch2 = vec![0i32; 30];
+    mapped
+        .read_channels(&channels(), &mut [&mut ch1, &mut ch2])
+        .unwrap();
+    assert_eq!(&ch1[..10], &[0, 2, 4, 6, 8, 10, 12, 14, 16, 18]);
+    assert_eq!(&ch2[20..], &[1, 3, 5, 7, 9, 11, 13, 15, 17, 19]);
+
+    assert!(matches!(
+        mapped.channel_slices::<i32>(&channels()[0]),
+        Err(TdmsError::ZeroCopyNotPossible(_))
+    ));
+}
+
+#[test]
+fn test_type_mismatch_errors() {
+    let path = TempPath::new("mmap-mismatch");
+    write_file(&path, &[1.0f64, 2.0], DataLayout::Contigious);
+    let mapped = MmapTdmsFile::open(&path.0).unwrap();
+
+    let mut output = vec![0i32; 1];
+    assert!(matches!(
+        mapped.read_channel(&channels()[0], &mut output),
+        Err(TdmsError::DataTypeMismatch(_, _))
+    ));
+    assert!(matches!(
+        mapped.channel_slices::<i32>(&channels()[0]),
+        Err(TdmsError::DataTypeMismatch(_, _))
+    ));
+}
+
+#[test]
+fn test_channel_slices_borrow_each_chunk() {
+    let path = TempPath::new("mmap-slices");
+    let values: Vec<u8> = (0..10).collect();
+    write_file(&path, &values, DataLayout::Contigious);
+    let mapped = MmapTdmsFile::open(&path.0).unwrap();
+
+    let slices = mapped.channel_slices::<u8>(&channels()[1]).unwrap();
+    assert_eq!(slices.len(), 3);
+    for slice in slices {
+        assert_eq!(slice, &[5, 6, 7, 8, 9]);
+    }
+}
+
+#[test]
+fn test_unaligned_block_uses_fallback() {
+    let path = TempPath::new("mmap-unaligned");
+    let channel = ChannelPath::new("group", "ch1");
+    let values: Vec<f64> = (0..10).map(|i| i as f64).collect();
+    let mut file = TdmsFile::create(&path.0).unwrap();
+    file.writer()
+        .unwrap()
+        .write_channels(&[&channel], &values, DataLayout::Contigious)
+        .unwrap();
+    // The lead in and meta data for this channel leave the data off an 8 byte boundary.
+    assert_ne!(file.data_block(0).unwrap().start % 8, 0);
+    drop(file);
+
+    let mapped = MmapTdmsFile::open(&path.0).unwrap();
+    assert!(matches!(
+        mapped.channel_slices::<f64>(&channel),
+        Err(TdmsError::ZeroCopyNotPossible(_))
+    ));
+
+    let mut output = vec![0.0f64; 10];
+    mapped.read_channel(&channel, &mut output).unwrap();
+    assert_eq!(output, values);
+}
+
+#[test]
+fn test_truncated_block_errors() {
+    let path = TempPath::new("mmap-truncated");
+    let values: Vec<f64> = (0..200).map(|i| i as f64).collect();
+    drop(write_file(&path, &values, DataLayout::Contigious));
+    // Cut off the end of the last block.
+    let length = std::fs::metadata(&path.0).unwrap().len();
+    File::options()
+        .write(true)
+        .open(&path.0)
+        .unwrap()
+        .set_len(length - 400)
+        .unwrap();
+
+    let mapped = MmapTdmsFile::open(&path.0).unwrap();
+    let mut output = vec![0.0f64; 300];
+    assert!(matches!(
+        mapped.read_channel(&channels()[1], &mut output),
+        Err(TdmsError::EndOfFile)
+    ));
+}
@@ -0,0 +1,114 @@
+//! Validate the different ways of opening files on disk.
+//!
+mod common;
+
+use common::TempPath;
+use tedium::{ChannelPath, DataLayout, TdmsError, TdmsFile};
+
+fn write_block(file: &mut TdmsFile<std::fs::File>, values: &[f64]) {
+    file.writer()
+        .unwrap()
+        .write_channels(
+            &[ChannelPath::new("group", "ch1")],
+            values,
+            DataLayout::Contigious,
+        )
+        .unwrap();
+}
+
+#[test]
+fn test_open_read_only() {
+    let path = TempPath::new("read-only");
+    let mut file = TdmsFile::create(&path.0).unwrap();
+    write_block(&mut file, &[1.0, 2.0, 3.0]);
+    drop(file);
+
+    let mut permissions = std::fs::metadata(&path.0).unwrap().permissions();
+    permissions.set_readonly(true);
+    std::fs::set_permissions(&path.0, permissions).unwrap();
+
+    let mut file = TdmsFile::open_read_only(&path.0).unwrap();
+    let mut output = vec![0.0f64; 3];
+    file.read_channel(&ChannelPath::new("group", "ch1"), &mut output[..])
+        .unwrap();
+    assert_eq!(output, vec![1.0, 2.0, 3.0]);
+}
+
+#[test]
+fn test_open_read_only_missing_file() {
+    let path = TempPath::new("read-only-missing");
+    assert!(TdmsFile::open_read_only(&path.0).is_err());
+    assert!(!path.0.exists());
+}
+
+#[test]
+fn test_open_append_existing() {
+    let path = TempPath::new("append-existing");
+    let mut file = TdmsFile::create(&path.0).unwrap();
+    write_block(&mut file, &[1.0, 2.0, 3.0]);
+    drop(file);
+
+    let mut file = TdmsFile::open_append(&path.0).unwrap();
+    write_block(&mut file, &[4.0, 5.0]);
+    drop(file);
+
+    let mut file = TdmsFile::open_read_only(&path.0).unwrap();
+    let channel = ChannelPath::new("group", "ch1");
+    assert_eq!(file.channel_length(&channel), Some(5));
+    let mut output = vec![0.0f64; 5];
+    file.read_channel(&channel, &mut output[..]).unwrap();
+    assert_eq!(output, vec![1.0, 2.0, 3.0, 4.0, 5.0]);
+}
+
+#[test]
+fn test_open_append_creates_file() {
+    let path = TempPath::new("append-new");
+    let mut file = TdmsFile::open_append(&path.0).unwrap();
+    write_block(&mut file, &[1.0]);
+    drop(file);
+
+    let file = TdmsFile::open_read_only(&path.0).unwrap();
+    assert_eq!(
+        file.channel_length(&ChannelPath::new("group", "ch1")),
+        Some(1)
+    );
+}
+
+#[test]
+fn test_second_locked_writer_fails() {
+    let path = TempPath::new("locked-writer");
+    let mut file = TdmsFile::create_locked(&path.0).unwrap();
+    write_block(&mut file, &[1.0, 2.0]);
+
+    let result = TdmsFile::open_append_locked(&path.0);
+    assert!(matches!(result, Err(TdmsError::FileLocked)));
+    let result = TdmsFile::create_locked(&path.0);
+    assert!(matches!(result, Err(TdmsError::FileLocked)));
+    let result = TdmsFile::open_read_only_locked(&path.0);
+    assert!(matches!(result, Err(TdmsError::FileLocked)));
+
+    // The failed create must not have truncated the file.
+    drop(file);
+    let file = TdmsFile::open_append_locked(&path.0).unwrap();
+    assert_eq!(
+        file.channel_length(&ChannelPath::new("group", "ch1")),
+        Some(2)
+    );
+}
+
+#[test]
+fn test_shared_readers_block_writer() {
+    let path = TempPath::new("locked-readers");
+    let mut file = TdmsFile::create(&path.0).unwrap();
+    write_block(&mut file, &[1.0]);
+    drop(file);
+
+    let reader1 = TdmsFile::open_read_only_locked(&path.0).unwrap();
+    let reader2 = TdmsFile::open_read_only_locked(&path.0).unwrap();
+    let result = TdmsFile::load_locked(&path.0);
+    assert!(matches!(result, Err(TdmsError::FileLocked)));
+
+    drop(reader1);
+    drop(reader2);
+    TdmsFile::load_locked(&path.0).unwrap();
+}
@@ -0,0 +1,85 @@
+//! Validate the owned writer which can be moved between threads.
+//!
+mod common;
+
+use common::get_empty_file;
+use tedium::{ChannelPath, DataLayout, PropertyPath, PropertyValue, TdmsWriterHandle};
+
+fn assert_send<T: Send>(_: &T) {}
+
+#[test]
+fn test_owned_writer_is_send() {
+    let writer = get_empty_file().into_writer().unwrap();
+    assert_send(&writer);
+}
+
+#[test]
+fn test_owned_writer_on_thread() {
+    let mut writer = get_empty_file().into_writer().unwrap();
+
+    let writer = std::thread::spawn(move || {
+        for block in 0..3 {
+            let data = [block as f64; 4];
+            writer
+                .write_channels(
+                    &[ChannelPath::new("group", "ch1")],
+                    &data[..],
+                    DataLayout::Contigious,
+                )
+                .unwrap();
+        }
+        writer
+            .write_properties(
+                &PropertyPath::group("group"),
+                &[("name", PropertyValue::String("logger".to_string()))],
+            )
+            .unwrap();
+        writer
+    })
+    .join()
+    .unwrap();
+
+    let mut file = writer.into_file().unwrap();
+
+    let channel = ChannelPath::new("group", "ch1");
+    assert_eq!(file.channel_length(&channel), Some(12));
+    let mut output = vec![0.0f64; 12];
+    file.read_channel(&channel, &mut output[..]).unwrap();
+    assert_eq!(
+        output,
+        vec![0.0, 0.0, 0.0, 0.0, 1.0, 1.0, 1.0, 1.0, 2.0, 2.0, 2.0, 2.0]
+    );
+    assert_eq!(
+        file.read_property(&PropertyPath::group("group"), "name")
+            .unwrap(),
+        Some(&PropertyValue::String("logger".to_string()))
+    );
+}
+
+#[test]
+fn test_owned_writer_appends_to_existing_data() {
+    let mut file = get_empty_file();
+    file.writer()
+        .unwrap()
+        .write_channels(
+            &[ChannelPath::new("group", "ch1")],
+            &[1.0, 2.0],
+            DataLayout::Contigious,
+        )
+        .unwrap();
+
+    let mut writer: TdmsWriterHandle<_> = file.into_writer().unwrap();
+    writer
+        .write_channels(
+            &[ChannelPath::new("group", "ch1")],
+            &[3.0, 4.0],
+            DataLayout::Contigious,
+        )
+        .unwrap();
+    let mut file = writer.into_file().unwrap();
+
+    let mut output = vec![0.0f64; 4];
+    file.read_channel(&ChannelPath::new("group", "ch1"), &mut output[..])
+        .unwrap();
+    assert_eq!(output, vec![1.0, 2.0, 3.0, 4.0]);
+}
@@ -0,0 +1,113 @@
+//! Rename groups and channels in files on disk.
+//!
+mod common;
+
+use std::path::PathBuf;
+
+use common::TempPath;
+use tedium::{ChannelPath, DataLayout, PropertyPath, PropertyValue, TdmsFile};
+
+fn write_test_file(path: &TempPath) -> Vec<f64> {
+    let mut file = TdmsFile::create(&path.0).unwrap();
+    let channels = [
+        ChannelPath::new("group", "ch1"),
+        ChannelPath::new("group", "ch2"),
+    ];
+    let values: Vec<f64> = (0..200).map(|i| i as f64).collect();
+    let mut writer = file.writer().unwrap();
+    writer
+        .write_properties(
+            &PropertyPath::channel("group", "ch1"),
+            &[("unit_string", PropertyValue::String("V".to_string()))],
+        )
+        .unwrap();
+    for _ in 0..5 {
+        writer
+            .write_channels(&channels, &values, DataLayout::Contigious)
+            .unwrap();
+    }
+    drop(writer);
+    values[..100].repeat(5)
+}
+
+fn read(file: &mut TdmsFile<std::fs::File>, channel: &ChannelPath) -> Vec<f64> {
+    let mut output = vec![0.0; file.channel_length(channel).unwrap() as usize];
+    file.read_channel(channel, &mut output).unwrap();
+    output
+}
+
+#[test]
+fn test_rename_same_length_channel() {
+    let path = TempPath::new("rename-same-length");
+    let expected = write_test_file(&path);
+    let length = std::fs::metadata(&path.0).unwrap().len();
+
+    TdmsFile::rename_object(
+        &path.0,
+        &PropertyPath::channel("group", "ch1"),
+        &PropertyPath::channel("group", "ch9"),
+    )
+    .unwrap();
+
+    assert_eq!(std::fs::metadata(&path.0).unwrap().len(), length);
+    let mut file = TdmsFile::load(&path.0).unwrap();
+    assert_eq!(read(&mut file, &ChannelPath::new("group", "ch9")), expected);
+    assert_eq!(file.channel_length(&ChannelPath::new("group", "ch1")), None);
+    assert_eq!(
+        file.read_property(&PropertyPath::channel("group", "ch9"), "unit_string")
+            .unwrap(),
+        Some(&PropertyValue::String("V".to_string()))
+    );
+}
+
+#[test]
+fn test_rename_group_with_copy() {
+    let path = TempPath::new("rename-copy");
+    let expected = write_test_file(&path);
+
+    TdmsFile::rename_object(
+        &path.0,
+        &PropertyPath::group("group"),
+        &PropertyPath::group("Bob's group"),
+    )
+    .unwrap();
+
+    let mut file = TdmsFile::load(&path.0).unwrap();
+    assert_eq!(
+        read(&mut file, &ChannelPath::new("Bob's group", "ch1")),
+        expected
+    );
+    assert_eq!(
+        file.channel_length(&ChannelPath::new("Bob's group", "ch2")),
+        Some(500)
+    );
+    assert_eq!(file.channel_length(&ChannelPath::new("group", "ch1")), None);
+
+    let mut temp_path = path.0.clone().into_os_string();
+    temp_path.push(".renaming");
+    assert!(!PathBuf::from(temp_path).exists());
+}
+
+#[test]
+fn test_rename_keeps_existing_temp_file() {
+    let path = TempPath::new("rename-existing-temp");
+    write_test_file(&path);
+    let mut temp_path = path.0.clone().into_os_string();
+    temp_path.push(".renaming");
+    let temp_path = TempPath(PathBuf::from(temp_path));
+    std::fs::write(&temp_path.0, b"not ours").unwrap();
+
+    let result = TdmsFile::rename_object(
+        &path.0,
+        &PropertyPath::group("group"),
+        &PropertyPath::group("Bob's group"),
+    );
+
+    assert!(result.is_err());
+    assert_eq!(std::fs::read(&temp_path.0).unwrap(), b"not ours");
+    let file = TdmsFile::load(&path.0).unwrap();
+    assert_eq!(
+        file.channel_length(&ChannelPath::new("group", "ch1")),
+        Some(500)
+    );
+}
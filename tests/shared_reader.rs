@@ -0,0 +1,84 @@
+//! Validate reading one file from multiple threads with the shared reader.
+//!
+mod common;
+
+use std::sync::Arc;
+
+use common::TempPath;
+use tedium::{ChannelPath, DataLayout, SharedTdmsFile, TdmsFile};
+
+const CHANNELS: usize = 4;
+const BLOCKS: usize = 10;
+const BLOCK_SIZE: usize = 100;
+
+fn channel(index: usize) -> ChannelPath {
+    ChannelPath::new("group", &format!("ch{index}"))
+}
+
+fn expected_value(channel: usize, sample: usize) -> f64 {
+    (channel * 100_000 + sample) as f64
+}
+
+fn write_test_file(path: &TempPath) -> TdmsFile<std::fs::File> {
+    let mut file = TdmsFile::create(&path.0).unwrap();
+    let channels: Vec<ChannelPath> = (0..CHANNELS).map(channel).collect();
+    let mut writer = file.writer().unwrap();
+    for block in 0..BLOCKS {
+        let values: Vec<f64> = (0..CHANNELS)
+            .flat_map(|ch| {
+                (0..BLOCK_SIZE).map(move |sample| expected_value(ch, block * BLOCK_SIZE + sample))
+            })
+            .collect();
+        writer
+            .write_channels(&channels, &values, DataLayout::Contigious)
+            .unwrap();
+    }
+    drop(writer);
+    file
+}
+
+#[test]
+fn test_shared_reads_from_threads() {
+    let path = TempPath::new("shared-threads");
+    let file = Arc::new(write_test_file(&path).into_shared());
+
+    let handles: Vec<_> = (0..CHANNELS)
+        .map(|ch| {
+            let file = file.clone();
+            std::thread::spawn(move || {
+                for _ in 0..20 {
+                    let mut output = vec![0.0f64; BLOCKS * BLOCK_SIZE];
+                    file.read_channel(&channel(ch), &mut output).unwrap();
+                    for (sample, value) in output.iter().enumerate() {
+                        assert_eq!(*value, expected_value(ch, sample));
+                    }
+                }
+            })
+        })
+        .collect();
+
+    for handle in handles {
+        handle.join().unwrap();
+    }
+}
+
+#[test]
+fn test_shared_open_read_from_offset() {
+    let path = TempPath::new("shared-open");
+    drop(write_test_file(&path));
+
+    let file = SharedTdmsFile::open(&path.0).unwrap();
+    assert_eq!(
+        file.channel_length(&channel(1)),
+        Some((BLOCKS * BLOCK_SIZE) as u64)
+    );
+
+    let mut ch1 = vec![0.0f64; 150];
+    let mut ch3 = vec![0.0f64; 150];
+    file.read_channels_from(&[channel(1), channel(3)], 250, &mut [&mut ch1, &mut ch3])
+        .unwrap();
+    assert_eq!(ch1[0], expected_value(1, 250));
+    assert_eq!(ch1[149], expected_value(1, 399));
+    assert_eq!(ch3[0], expected_value(3, 250));
+    assert_eq!(ch3[149], expected_value(3, 399));
+}
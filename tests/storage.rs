@@ -0,0 +1,61 @@
+//! Validate the storage backed file on the local filesystem.
+//!
+#![cfg(any(unix, windows))]
+mod common;
+
+use common::TempPath;
+use tedium::{ChannelPath, DataLayout, FileStorage, TdmsFile};
+
+#[test]
+fn test_file_storage_round_trip() {
+    let path = TempPath::new("file-storage");
+    let channel = ChannelPath::new("group", "ch1");
+
+    let mut file = TdmsFile::from_storage(FileStorage::create(&path.0).unwrap()).unwrap();
+    let mut writer = file.writer().unwrap();
+    writer
+        .write_channels(&[&channel], &[1.0, 2.0, 3.0], DataLayout::Contigious)
+        .unwrap();
+    writer.sync().unwrap();
+    drop(writer);
+    drop(file);
+
+    // Append to the existing file.
+    let mut file = TdmsFile::from_storage(FileStorage::open(&path.0).unwrap()).unwrap();
+    let mut writer = file.writer().unwrap();
+    writer
+        .write_channels(&[&channel], &[4.0, 5.0], DataLayout::Contigious)
+        .unwrap();
+    writer.sync().unwrap();
+    drop(writer);
+    drop(file);
+
+    let mut file = TdmsFile::from_storage(FileStorage::open_read_only(&path.0).unwrap()).unwrap();
+    let mut output = [0.0f64; 5];
+    file.read_channel(&channel, &mut output).unwrap();
+    assert_eq!(output, [1.0, 2.0, 3.0, 4.0, 5.0]);
+
+    // The standard reader sees the same data.
+    let mut file = TdmsFile::load(&path.0).unwrap();
+    let mut output = [0.0f64; 5];
+    file.read_channel(&channel, &mut output).unwrap();
+    assert_eq!(output, [1.0, 2.0, 3.0, 4.0, 5.0]);
+}
+
+#[test]
+fn test_read_only_file_storage_rejects_append() {
+    let path = TempPath::new("file-storage-read-only");
+    drop(FileStorage::create(&path.0).unwrap());
+
+    let mut file = TdmsFile::from_storage(FileStorage::open_read_only(&path.0).unwrap()).unwrap();
+    let mut writer = file.writer().unwrap();
+    // The segment is buffered so the failure may only show when it is synced.
+    let result = writer
+        .write_channels(
+            &[ChannelPath::new("group", "ch1")],
+            &[1.0],
+            DataLayout::Contigious,
+        )
+        .and_then(|_| writer.sync());
+    assert!(result.is_err());
+}
@@ -118,6 +118,54 @@ fn test_repeated_writes() {
     assert_eq!(buffer, vec![4.0, 5.0, 6.0, 10.0, 11.0, 12.0]);
 }
 
+#[test]
+fn test_write_subset_of_previous_channels() {
+    let channel1 = ChannelPath::new("structure", "ch1");
+    let channel2 = ChannelPath::new("structure", "ch2");
+
+    let mut file = get_empty_file();
+    let mut writer = file.writer().unwrap();
+    writer
+        .write_channels(
+            &[&channel1, &channel2],
+            &[1.0, 2.0, 3.0, 4.0],
+            DataLayout::Contigious,
+        )
+        .unwrap();
+    // Only the first channel matches the previous list so this needs new meta data.
+    writer
+        .write_channels(&[&channel1], &[5.0, 6.0], DataLayout::Contigious)
+        .unwrap();
+    drop(writer);
+
+    assert_eq!(file.channel_length(&channel1), Some(4));
+    assert_eq!(file.channel_length(&channel2), Some(2));
+    let mut buffer = vec![0.0; 4];
+    file.read_channel(&channel1, &mut buffer[..]).unwrap();
+    assert_eq!(buffer, vec![1.0, 2.0, 5.0, 6.0]);
+}
+
+#[test]
+fn test_write_same_channels_with_new_length() {
+    let channel1 = ChannelPath::new("structure", "ch1");
+
+    let mut file = get_empty_file();
+    let mut writer = file.writer().unwrap();
+    writer
+        .write_channels(&[&channel1], &[1.0, 2.0], DataLayout::Contigious)
+        .unwrap();
+    // The channel list matches but the number of values doesn't.
+    writer
+        .write_channels(&[&channel1], &[3.0, 4.0, 5.0], DataLayout::Contigious)
+        .unwrap();
+    drop(writer);
+
+    assert_eq!(file.channel_length(&channel1), Some(5));
+    let mut buffer = vec![0.0; 5];
+    file.read_channel(&channel1, &mut buffer[..]).unwrap();
+    assert_eq!(buffer, vec![1.0, 2.0, 3.0, 4.0, 5.0]);
+}
+
 #[test]
 fn write_with_no_channels_error() {
     let mut file = get_empty_file();